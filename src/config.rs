@@ -1,65 +1,585 @@
 // src/config.rs
+use regex::{Captures, Regex};
 use serde::Deserialize;
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Clone)]
+/// Примусовий генератор для колонки, що замінює рішення Gemini.
+/// `generator = "gemini"` разом із `prompt` створює окремий пул даних, заповнений за цим промптом.
+/// Лише `weights` (без `generator`) задає частоти значень enum-колонки для генератора `choice`.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct ColumnOverride {
+    #[serde(default)]
     pub generator: String,
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
     pub prompt: Option<String>,
+    /// Застаріла форма параметра `type`; передається генератору як `params.type`
     #[serde(rename = "type")]
     pub kind: Option<String>,
+    /// Відносні ваги значень для `choice`, наприклад `{ completed = 0.8, pending = 0.15 }`
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 pub struct SeedingTask {
     pub table: String,
-    pub rows: u32,
+    pub rows: Rows,
     // Ці поля є залишками старої системи, але ми їх залишимо,
     // щоб не ламати парсинг старих конфігів. Вони ігноруються в новій логіці.
     #[serde(default)]
+    #[allow(dead_code)]
     pub columns: Option<Vec<String>>,
     #[serde(default)]
     pub column_overrides: HashMap<String, ColumnOverride>,
     #[serde(default)]
+    #[allow(dead_code)]
     pub smart_mode: bool,
+    /// Ймовірність (0.0..1.0), що рядок отримає посилання на "батька" з цієї ж таблиці
+    /// для самопосилальних FK (наприклад, `categories.parent_id`). За замовчуванням 0.5.
+    #[serde(default)]
+    pub self_reference_chance: Option<f64>,
+    /// Розкид кількості рядків (частка, 0.0 - 1.0): з 0.2 фактична кількість обирається випадково
+    /// в межах ±20% від `rows`. За замовчуванням 0 — рівно `rows`
+    #[serde(default)]
+    pub rows_jitter: f64,
+    /// Зерно генератора випадкових чисел для цієї таблиці (комбінується з `generation.seed`), щоб
+    /// перегенерувати лише її дані відтворювано, не змінюючи інших таблиць
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Ім'я підключення з `[databases]`, у яке заповнюється таблиця. Без нього — основне `[database]`
+    #[serde(default)]
+    pub database: Option<String>,
+}
+
+/// Кількість рядків таблиці: число або `"3x orders"` — втричі більше, ніж фактично вставлено в `orders`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rows {
+    Fixed(u32),
+    /// Розраховується сідером після заповнення батьківської таблиці
+    PerParent { factor: f64, parent: String },
+}
+
+impl Default for Rows {
+    fn default() -> Self {
+        Rows::Fixed(0)
+    }
+}
+
+impl Rows {
+    /// Кількість рядків для `Fixed`. Відносну кількість сідер замінює на `Fixed` перед заповненням
+    pub fn count(&self) -> u32 {
+        match self {
+            Rows::Fixed(rows) => *rows,
+            Rows::PerParent { .. } => 0,
+        }
+    }
+
+    /// Батьківська таблиця відносної кількості рядків
+    pub fn parent(&self) -> Option<&str> {
+        match self {
+            Rows::Fixed(_) => None,
+            Rows::PerParent { parent, .. } => Some(parent),
+        }
+    }
+
+    /// `factor` разів по `parent_rows`, округлено
+    pub fn resolve(&self, parent_rows: u64) -> u32 {
+        match self {
+            Rows::Fixed(rows) => *rows,
+            Rows::PerParent { factor, .. } => (parent_rows as f64 * factor).round().clamp(0.0, u32::MAX as f64) as u32,
+        }
+    }
+}
+
+impl std::fmt::Display for Rows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rows::Fixed(rows) => write!(f, "{}", rows),
+            Rows::PerParent { factor, parent } => write!(f, "{}x {}", factor, parent),
+        }
+    }
+}
+
+impl std::str::FromStr for Rows {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Ok(rows) = value.parse::<u32>() {
+            return Ok(Rows::Fixed(rows));
+        }
+        let invalid = || format!("невірне значення rows '{}': очікується число або \"<множник>x <таблиця>\", наприклад \"3x orders\"", value);
+        // "3x orders", "3 x orders", "0.5x orders"
+        let number_end = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).ok_or_else(invalid)?;
+        let factor: f64 = value[..number_end].parse().map_err(|_| invalid())?;
+        let parent = value[number_end..].trim_start().strip_prefix(['x', 'X']).ok_or_else(invalid)?.trim();
+        if !factor.is_finite() || parent.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Rows::PerParent { factor, parent: parent.to_string() })
+    }
 }
 
-#[derive(Debug, Deserialize)]
+impl<'de> Deserialize<'de> for Rows {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u32),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(rows) => Ok(Rows::Fixed(rows)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
 pub struct DatabaseConfig {
     pub url: String,
+    /// Схема Postgres, з якою працює сідер (за замовчуванням `public`)
+    pub schema: Option<String>,
+    /// Максимальна кількість з'єднань у пулі (за замовчуванням 10)
+    pub max_connections: Option<u32>,
+    /// Скільки секунд чекати на вільне з'єднання з пулу (за замовчуванням 30)
+    pub acquire_timeout_secs: Option<u64>,
+    /// Скільки секунд чекати на встановлення першого з'єднання (за замовчуванням 30)
+    pub connect_timeout_secs: Option<u64>,
+    /// Файл з паролем (Docker/Kubernetes secrets). Пароль з файлу має пріоритет над паролем з `url`
+    pub password_file: Option<String>,
+    /// Пароль, прочитаний з `password_file` під час завантаження конфігурації
+    #[serde(skip)]
+    pub password: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default, PartialEq)]
 pub struct GeminiConfig {
     pub model: String,
+    /// Ключ API, якщо змінна середовища не задана (змінна середовища має пріоритет)
+    pub api_key: Option<String>,
+    /// Назва змінної середовища з ключем замість `GEMINI_API_KEY`
+    pub api_key_env: Option<String>,
     pub temperature: Option<f32>,
+    /// Nucleus sampling (0.0 - 1.0)
+    pub top_p: Option<f32>,
+    /// Максимальна довжина відповіді в токенах. Для широких схем план може не вміститися в ліміт моделі за замовчуванням
+    pub max_output_tokens: Option<u32>,
+    /// Максимальна кількість запитів до Gemini за запуск (план + кожен пул). Без ліміту, якщо не задано
+    pub max_api_calls: Option<u64>,
+    /// Каталог для кешу пулів даних між запусками. Якщо не задано — пули щоразу генеруються заново
+    pub pool_cache_dir: Option<String>,
+    /// Заповнювати пули з відомими назвами (`first_names`, `emails`, `cities` тощо) локально через `fake`,
+    /// не витрачаючи запити до Gemini
+    #[serde(default)]
+    pub prefer_local_pools: bool,
+    /// Адреса API замість публічної `https://generativelanguage.googleapis.com/v1beta`, наприклад
+    /// внутрішній шлюз. До неї додається `/models/{model}:generateContent?key=...`
+    pub base_url: Option<String>,
+    /// Проксі для запитів до Gemini, наприклад `http://proxy.corp:3128`. Без нього — змінна середовища `HTTPS_PROXY`
+    pub proxy_url: Option<String>,
+    /// Не перевіряти TLS-сертифікати (для корпоративних проксі, що підміняють сертифікати). Небезпечно!
+    #[serde(default)]
+    pub danger_accept_invalid_certs: bool,
+    /// Ціна в доларах за мільйон вхідних токенів, для оцінки вартості запуску
+    pub input_price_per_million: Option<f64>,
+    /// Ціна в доларах за мільйон вихідних токенів
+    pub output_price_per_million: Option<f64>,
 }
 
 // ВИПРАВЛЕНО: Нова секція для налаштувань генерації
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct GenerationConfig {
     pub language: String,
+    /// Допустима відносна розбіжність між запитаною та фактичною кількістю рядків (0.0 - 1.0),
+    /// після перевищення якої виводиться попередження. За замовчуванням 0.0 — попереджати про будь-яку.
+    #[serde(default)]
+    pub count_mismatch_threshold: f64,
+    /// Скільки разів перегенерувати рядок, що порушив обмеження БД (unique, NOT NULL, CHECK, FK),
+    /// перш ніж зупинити заповнення з помилкою. За замовчуванням 3.
+    #[serde(default = "default_row_retries")]
+    pub row_retries: u32,
+    /// Скільки разів заповнювати таблицю з нуля після дедлоку або конфлікту серіалізації. За замовчуванням 3.
+    /// У режимі `TransactionMode::Global` не застосовується: така помилка скасовує всю транзакцію запуску.
+    #[serde(default = "default_transient_retries")]
+    pub transient_retries: u32,
+    #[serde(default)]
+    pub transaction_mode: TransactionMode,
+    /// Не генерувати значення для колонок з DEFAULT у БД (наприклад, `created_at DEFAULT now()`),
+    /// навіть якщо Gemini включив їх у план. `column_overrides` при цьому все одно застосовуються.
+    #[serde(default)]
+    pub prefer_db_defaults: bool,
+    /// Дозаповнення: генератор `sequence` для первинного ключа продовжує після `MAX(pk)` таблиці,
+    /// щоб нові рядки не конфліктували з уже заповненими
+    #[serde(default)]
+    pub append: bool,
+    /// Зерно генератора випадкових чисел: з тим самим планом і пулами дані відтворюються від запуску до запуску
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Колонки, значення яких приховуються в повідомленнях про помилки вставки: `password_hash` або `users.token`
+    #[serde(default)]
+    pub redact_columns: Vec<String>,
+    /// Запускати `ANALYZE` для заповнених таблиць після завершення, щоб планувальник одразу мав статистику
+    #[serde(default)]
+    pub analyze_after: bool,
+    /// `VACUUM ANALYZE` замість `ANALYZE` (довше, але ще й оновлює visibility map для великих обсягів)
+    #[serde(default)]
+    pub vacuum_analyze: bool,
+    /// Ручний порядок заповнення таблиць замість автоматичного. Не вказані таблиці заповнюються після
+    /// вказаних в автоматичному порядку; порядок, що порушує залежності між таблицями, — помилка
+    #[serde(default)]
+    pub table_order: Vec<String>,
+    /// Скільки наявних ключів завантажувати з заповненої батьківської таблиці поза планом (і з таблиць,
+    /// пропущених через --seed-only-empty), щоб `fk` дочірніх таблиць посилалися на них
+    #[serde(default = "default_existing_pks_limit")]
+    pub existing_pks_limit: u32,
+    /// Запобіжник: найбільша кількість рядків для будь-якої таблиці (після `rows_jitter` і відносної кількості),
+    /// щоб помилка в плані на кшталт `rows = 10000000` не заповнила диск
+    #[serde(default)]
+    pub max_rows_per_table: Option<u32>,
+}
+
+/// Як групувати вставки в транзакції
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionMode {
+    /// Кожна таблиця в окремій транзакції: помилка посередині залишає вже заповнені таблиці
+    #[default]
+    PerTable,
+    /// Одна транзакція на весь запуск: або заповнено все, або нічого
+    Global,
+}
+
+pub const DEFAULT_ROW_RETRIES: u32 = 3;
+pub const DEFAULT_TRANSIENT_RETRIES: u32 = 3;
+pub const DEFAULT_EXISTING_PKS_LIMIT: u32 = 10_000;
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            count_mismatch_threshold: 0.0,
+            row_retries: DEFAULT_ROW_RETRIES,
+            transient_retries: DEFAULT_TRANSIENT_RETRIES,
+            transaction_mode: TransactionMode::default(),
+            prefer_db_defaults: false,
+            append: false,
+            redact_columns: Vec::new(),
+            seed: None,
+            analyze_after: false,
+            vacuum_analyze: false,
+            table_order: Vec::new(),
+            existing_pks_limit: DEFAULT_EXISTING_PKS_LIMIT,
+            max_rows_per_table: None,
+        }
+    }
+}
+
+fn default_row_retries() -> u32 {
+    DEFAULT_ROW_RETRIES
+}
+
+fn default_transient_retries() -> u32 {
+    DEFAULT_TRANSIENT_RETRIES
+}
+
+fn default_existing_pks_limit() -> u32 {
+    DEFAULT_EXISTING_PKS_LIMIT
 }
 
 // ВИПРАВЛЕНО: Єдина, правильна структура AppConfig
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Default, PartialEq)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
+    /// Додаткові іменовані підключення, щоб заповнити кілька БД за один запуск (FK між ними не підтримуються)
+    #[serde(default)]
+    pub databases: HashMap<String, DatabaseConfig>,
     pub gemini: Option<GeminiConfig>,
     pub generation: Option<GenerationConfig>,
 
-    #[serde(rename = "seeding_plan")]
+    /// План обраного профілю; заповнюється з `seeding_plan` під час завантаження (див. `--profile`)
+    #[serde(skip)]
     pub plan: Option<Vec<SeedingTask>>,
+    #[serde(rename = "seeding_plan")]
+    plan_section: Option<PlanSection>,
     pub default_rows: Option<u32>,
 }
 
+/// Секція `seeding_plan`: звичайний список `[[seeding_plan]]` або іменовані профілі
+/// `[[seeding_plan.smoke]]`, `[[seeding_plan.load]]`, між якими перемикає `--profile`
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum PlanSection {
+    Flat(Vec<SeedingTask>),
+    Profiles(HashMap<String, Vec<SeedingTask>>),
+}
+
+/// Профіль плану, що використовується без `--profile`
+pub const DEFAULT_PROFILE: &str = "default";
+
 impl AppConfig {
-    pub fn from_file(path: &str) -> crate::error::AppResult<Self> {
+    /// Завантажує конфігурацію та обирає профіль плану (`None` — профіль `default` або плаский `[[seeding_plan]]`)
+    pub fn from_file(path: &str, profile: Option<&str>) -> crate::error::AppResult<Self> {
         let builder = config::Config::builder()
-            .add_source(config::File::with_name(path).required(true))
+            .add_source(Self::file_source(path)?.required(true))
             .add_source(config::Environment::with_prefix("APP"));
             
-        let config = builder.build()?.try_deserialize()?;
+        let mut config: Self = builder.build()?.try_deserialize()?;
+        config.database.resolve_secrets()?;
+        for database in config.databases.values_mut() {
+            database.resolve_secrets()?;
+        }
+        config.plan = config.select_profile(profile)?;
         Ok(config)
     }
-}
\ No newline at end of file
+
+    fn select_profile(&mut self, profile: Option<&str>) -> crate::error::AppResult<Option<Vec<SeedingTask>>> {
+        let name = profile.unwrap_or(DEFAULT_PROFILE);
+        match self.plan_section.take() {
+            None if profile.is_some() => Err(crate::error::AppError::Custom(format!(
+                "Профіль '{}' не знайдено: у конфігурації немає секції seeding_plan",
+                name
+            ))),
+            None => Ok(None),
+            Some(PlanSection::Flat(tasks)) if name == DEFAULT_PROFILE => Ok(Some(tasks)),
+            Some(PlanSection::Flat(_)) => Err(crate::error::AppError::Custom(format!(
+                "Профіль '{}' не знайдено: конфігурація містить лише звичайний [[seeding_plan]] без профілів",
+                name
+            ))),
+            Some(PlanSection::Profiles(mut profiles)) => profiles.remove(name).map(Some).ok_or_else(|| {
+                let mut available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                available.sort_unstable();
+                crate::error::AppError::Custom(format!(
+                    "Профіль '{}' не знайдено в seeding_plan. Доступні профілі: {}",
+                    name,
+                    available.join(", ")
+                ))
+            }),
+        }
+    }
+
+    /// Обирає формат файлу за розширенням (.toml, .yaml/.yml, .json).
+    /// Шлях без розширення передаємо `config` як є — він сам знайде файл з відомим розширенням.
+    fn file_source(path: &str) -> crate::error::AppResult<config::File<config::FileSourceFile, config::FileFormat>> {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        let format = match extension.as_deref() {
+            None => return Ok(config::File::with_name(path)),
+            Some("toml") => config::FileFormat::Toml,
+            Some("yaml" | "yml") => config::FileFormat::Yaml,
+            Some("json") => config::FileFormat::Json,
+            Some(other) => {
+                return Err(crate::error::AppError::Custom(format!(
+                    "Непідтримуваний формат конфігурації '.{}': використовуйте .toml, .yaml/.yml або .json",
+                    other
+                )))
+            }
+        };
+        Ok(config::File::new(path, format))
+    }
+}
+
+impl DatabaseConfig {
+    /// Підставляє змінні середовища в `url` та читає `password_file`
+    fn resolve_secrets(&mut self) -> crate::error::AppResult<()> {
+        self.url = expand_env_vars(&self.url)?;
+        if let Some(password_file) = &self.password_file {
+            self.password = Some(read_password_file(password_file)?);
+        }
+        Ok(())
+    }
+}
+
+/// Читає пароль з файлу, відкидаючи кінцеві переводи рядка, які зазвичай лишають редактори та `echo`
+fn read_password_file(path: &str) -> crate::error::AppResult<String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        crate::error::AppError::Custom(format!("Не вдалося прочитати database.password_file '{}': {}", path, e))
+    })?;
+    Ok(content.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Підставляє змінні середовища у форматі `${NAME}`. Якщо хоча б одна змінна не задана — помилка з їх переліком.
+fn expand_env_vars(value: &str) -> crate::error::AppResult<String> {
+    let pattern = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("регулярний вираз для змінних середовища має бути валідним");
+    let mut missing = Vec::new();
+
+    let expanded = pattern.replace_all(value, |caps: &Captures| {
+        std::env::var(&caps[1]).unwrap_or_else(|_| {
+            missing.push(caps[1].to_string());
+            String::new()
+        })
+    });
+
+    if !missing.is_empty() {
+        return Err(crate::error::AppError::Custom(format!(
+            "database.url посилається на незадані змінні середовища: {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(expanded.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Записує конфігурацію у тимчасовий файл з розширенням `extension` і завантажує її
+    fn load(name: &str, extension: &str, contents: &str, profile: Option<&str>) -> crate::error::AppResult<AppConfig> {
+        let path = std::env::temp_dir().join(format!("db_seeder_config_{}_{}.{}", name, std::process::id(), extension));
+        std::fs::write(&path, contents).unwrap();
+        let config = AppConfig::from_file(path.to_str().unwrap(), profile);
+        std::fs::remove_file(&path).ok();
+        config
+    }
+
+    const TOML: &str = r#"
+default_rows = 7
+
+[database]
+url = "postgres://localhost/shop"
+schema = "sales"
+
+[gemini]
+model = "gemini-1.5-flash"
+max_api_calls = 5
+
+[generation]
+language = "uk"
+seed = 42
+redact_columns = ["password_hash"]
+
+[[seeding_plan]]
+table = "users"
+rows = 10
+rows_jitter = 0.2
+
+[[seeding_plan]]
+table = "orders"
+rows = "3x users"
+
+[seeding_plan.column_overrides.status]
+generator = "choice"
+params = { options = ["new", "paid"] }
+weights = { new = 0.75, paid = 0.25 }
+"#;
+
+    const YAML: &str = r#"
+default_rows: 7
+database:
+  url: postgres://localhost/shop
+  schema: sales
+gemini:
+  model: gemini-1.5-flash
+  max_api_calls: 5
+generation:
+  language: uk
+  seed: 42
+  redact_columns: [password_hash]
+seeding_plan:
+  - table: users
+    rows: 10
+    rows_jitter: 0.2
+  - table: orders
+    rows: 3x users
+    column_overrides:
+      status:
+        generator: choice
+        params:
+          options: [new, paid]
+        weights:
+          new: 0.75
+          paid: 0.25
+"#;
+
+    const JSON: &str = r#"{
+  "default_rows": 7,
+  "database": { "url": "postgres://localhost/shop", "schema": "sales" },
+  "gemini": { "model": "gemini-1.5-flash", "max_api_calls": 5 },
+  "generation": { "language": "uk", "seed": 42, "redact_columns": ["password_hash"] },
+  "seeding_plan": [
+    { "table": "users", "rows": 10, "rows_jitter": 0.2 },
+    {
+      "table": "orders",
+      "rows": "3x users",
+      "column_overrides": {
+        "status": { "generator": "choice", "params": { "options": ["new", "paid"] }, "weights": { "new": 0.75, "paid": 0.25 } }
+      }
+    }
+  ]
+}"#;
+
+    #[test]
+    fn formats_load_identically() {
+        let toml = load("formats", "toml", TOML, None).unwrap();
+        let yaml = load("formats", "yaml", YAML, None).unwrap();
+        let json = load("formats", "json", JSON, None).unwrap();
+        assert_eq!(toml, yaml);
+        assert_eq!(toml, json);
+
+        let plan = toml.plan.as_deref().unwrap();
+        assert_eq!(plan[0].rows, Rows::Fixed(10));
+        assert_eq!(plan[1].rows, Rows::PerParent { factor: 3.0, parent: "users".to_string() });
+        assert_eq!(plan[1].column_overrides["status"].weights["new"], 0.75);
+        assert_eq!(toml.database.schema.as_deref(), Some("sales"));
+        assert_eq!(toml.generation.as_ref().map(|g| g.row_retries), Some(DEFAULT_ROW_RETRIES));
+    }
+
+    #[test]
+    fn unknown_extension_is_rejected() {
+        let error = load("formats", "ini", "", None).unwrap_err();
+        assert!(error.to_string().contains(".ini"), "{}", error);
+    }
+
+    const PROFILES_TOML: &str = r#"
+[database]
+url = "postgres://localhost/shop"
+
+[[seeding_plan.default]]
+table = "users"
+rows = 10
+
+[[seeding_plan.smoke]]
+table = "users"
+rows = 2
+
+[[seeding_plan.load]]
+table = "users"
+rows = 10000
+"#;
+
+    const PROFILES_YAML: &str = r#"
+database:
+  url: postgres://localhost/shop
+seeding_plan:
+  default:
+    - { table: users, rows: 10 }
+  smoke:
+    - { table: users, rows: 2 }
+  load:
+    - { table: users, rows: 10000 }
+"#;
+
+    #[test]
+    fn profiles_select_plan() {
+        for (extension, contents) in [("toml", PROFILES_TOML), ("yaml", PROFILES_YAML)] {
+            let rows = |profile| load("profiles", extension, contents, profile).unwrap().plan.unwrap()[0].rows.clone();
+            assert_eq!(rows(None), Rows::Fixed(10));
+            assert_eq!(rows(Some("smoke")), Rows::Fixed(2));
+            assert_eq!(rows(Some("load")), Rows::Fixed(10000));
+
+            let error = load("profiles", extension, contents, Some("nightly")).unwrap_err().to_string();
+            assert!(error.contains("default, load, smoke"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn flat_plan_has_only_default_profile() {
+        assert_eq!(load("flat", "toml", TOML, Some(DEFAULT_PROFILE)).unwrap().plan.map(|p| p.len()), Some(2));
+        let error = load("flat", "toml", TOML, Some("smoke")).unwrap_err().to_string();
+        assert!(error.contains("без профілів"), "{}", error);
+    }
+}
@@ -1,8 +1,13 @@
 // src/db.rs
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use regex::Regex;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgRow};
+use sqlx::{PgConnection, Pool, Postgres, Row};
 use std::collections::HashMap;
+use std::time::Duration;
+use tracing::debug;
 
-use crate::error::AppResult;
+use crate::config::DatabaseConfig;
+use crate::error::{AppError, AppResult};
 
 // --- Структури для опису схеми БД ---
 
@@ -12,6 +17,38 @@ pub struct ColumnSchema {
     pub data_type: String,
     pub is_nullable: bool,
     pub column_default: Option<String>,
+    /// Схема та ім'я типу в Postgres (для `USER-DEFINED` це ім'я enum/composite типу)
+    pub udt_schema: String,
+    pub udt_name: String,
+    /// Допустимі значення, якщо колонка має тип enum
+    pub enum_labels: Vec<String>,
+    /// Максимальна довжина в символах для `varchar(n)` / `char(n)`
+    pub max_length: Option<i32>,
+    /// DOMAIN, яким оголошено колонку (`email_address`). `data_type` та `udt_name` при цьому — базовий тип домену,
+    /// а CHECK домену додається до `check_constraints` таблиці
+    pub domain: Option<(String, String)>,
+    /// Значення обчислює сама БД: `GENERATED ... AS IDENTITY` або `GENERATED ALWAYS AS (...) STORED`
+    pub db_generated: bool,
+    /// БД не приймає явних значень: `GENERATED ALWAYS AS IDENTITY` або обчислювана колонка.
+    /// `GENERATED BY DEFAULT AS IDENTITY` сюди не входить
+    pub db_generated_always: bool,
+}
+
+impl ColumnSchema {
+    /// Чи заповнить БД колонку сама, якщо вона відсутня в INSERT: будь-який DEFAULT
+    /// (`nextval`, константа чи функція на кшталт `now()`), IDENTITY або обчислювана колонка
+    pub fn has_db_default(&self) -> bool {
+        self.column_default.is_some() || self.db_generated
+    }
+
+    pub fn is_enum(&self) -> bool {
+        !self.enum_labels.is_empty()
+    }
+
+    /// Повне ім'я типу колонки для явного приведення: `"schema"."type"`
+    pub fn qualified_type(&self) -> String {
+        format!("\"{}\".\"{}\"", self.udt_schema, self.udt_name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -22,11 +59,90 @@ pub struct ForeignKey {
     pub to_column: String,
 }
 
+/// CHECK обмеження таблиці у вигляді, який повертає `pg_get_constraintdef`
+#[derive(Debug, Clone)]
+pub struct CheckConstraint {
+    pub name: String,
+    /// Наприклад, `CHECK ((age >= 0))`
+    pub definition: String,
+    /// Колонки, які згадуються в обмеженні
+    pub columns: Vec<String>,
+}
+
+impl CheckConstraint {
+    /// Допустимі значення для обмежень виду `col IN ('a', 'b')`, які Postgres зберігає як `col = ANY (ARRAY[...])`.
+    /// Повертає `None`, якщо обмеження стосується кількох колонок або має іншу форму.
+    pub fn allowed_values(&self) -> Option<Vec<String>> {
+        if self.columns.len() != 1 || !self.definition.contains("= ANY") {
+            return None;
+        }
+        let array_re = Regex::new(r"ARRAY\[(.*?)\]").unwrap();
+        let literal_re = Regex::new(r"'((?:[^']|'')*)'|(-?\d+(?:\.\d+)?)").unwrap();
+
+        let items = array_re.captures(&self.definition)?.get(1)?.as_str();
+        let values: Vec<String> = literal_re
+            .captures_iter(items)
+            .filter_map(|caps| match (caps.get(1), caps.get(2)) {
+                (Some(text), _) => Some(text.as_str().replace("''", "'")),
+                (None, Some(number)) => Some(number.as_str().to_string()),
+                _ => None,
+            })
+            .collect();
+        (!values.is_empty()).then_some(values)
+    }
+
+    /// Межі цілих значень з обмежень виду `col > 0`, `col >= 1 AND col <= 100` (нижня, верхня).
+    /// Обмеження з OR чи кількома колонками не розбираються.
+    pub fn numeric_bounds(&self) -> (Option<i64>, Option<i64>) {
+        if self.columns.len() != 1 || self.definition.contains(" OR ") {
+            return (None, None);
+        }
+        let comparison_re = Regex::new(r"(>=|<=|>|<)\s*\(?'?(-?\d+(?:\.\d+)?)").unwrap();
+        let (mut lower, mut upper) = (None::<i64>, None::<i64>);
+        for caps in comparison_re.captures_iter(&self.definition) {
+            // Дробові межі (`price > 0.5`) пропускаємо: `number_range` генерує цілі
+            let Ok(number) = caps[2].parse::<i64>() else { continue };
+            match &caps[1] {
+                ">" => lower = Some(lower.map_or(number + 1, |l| l.max(number + 1))),
+                ">=" => lower = Some(lower.map_or(number, |l| l.max(number))),
+                "<" => upper = Some(upper.map_or(number - 1, |u| u.min(number - 1))),
+                _ => upper = Some(upper.map_or(number, |u| u.min(number))),
+            }
+        }
+        (lower, upper)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TableSchema {
     pub name: String,
     pub columns: Vec<ColumnSchema>,
     pub primary_key_column: Option<String>,
+    pub check_constraints: Vec<CheckConstraint>,
+}
+
+impl TableSchema {
+    /// Допустимі значення колонки з CHECK `IN (...)`, якщо таке обмеження є
+    pub fn check_allowed_values(&self, column_name: &str) -> Option<Vec<String>> {
+        self.check_constraints
+            .iter()
+            .filter(|c| c.columns.iter().any(|col| col == column_name))
+            .find_map(CheckConstraint::allowed_values)
+    }
+
+    /// Найвужчі межі цілих значень колонки з усіх її CHECK обмежень (таблиці та домену)
+    pub fn check_bounds(&self, column_name: &str) -> (Option<i64>, Option<i64>) {
+        self.check_constraints
+            .iter()
+            .filter(|c| c.columns.iter().any(|col| col == column_name))
+            .map(CheckConstraint::numeric_bounds)
+            .fold((None, None), |(lower, upper), (l, u)| {
+                (lower.max(l), match (upper, u) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                })
+            })
+    }
 }
 
 pub struct DbSchema {
@@ -38,25 +154,113 @@ pub struct DbSchema {
 
 pub struct DbClient {
     pool: Pool<Postgres>,
+    schema: String,
 }
 
 impl DbClient {
     /// Створює новий екземпляр клієнта та підключається до БД
-    pub async fn new(db_url: &str) -> AppResult<Self> {
-        let pool = Pool::<Postgres>::connect(db_url).await?;
-        Ok(Self { pool })
+    pub async fn new(config: &DatabaseConfig) -> AppResult<Self> {
+        let connect_timeout = Duration::from_secs(config.connect_timeout_secs.unwrap_or(30));
+        let options = PgPoolOptions::new()
+            .max_connections(config.max_connections.unwrap_or(10))
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs.unwrap_or(30)));
+
+        let mut connect_options: PgConnectOptions = config.url.parse()?;
+        if let Some(password) = &config.password {
+            connect_options = connect_options.password(password);
+        }
+
+        let pool = tokio::time::timeout(connect_timeout, options.connect_with(connect_options))
+            .await
+            .map_err(|_| AppError::Custom(format!("Не вдалося підключитися до БД за {} с", connect_timeout.as_secs())))??;
+        let client = Self::from_pool(pool);
+        Ok(match &config.schema {
+            Some(schema) => client.with_schema(schema.as_str()),
+            None => client,
+        })
+    }
+
+    /// Клієнт поверх уже відкритого пулу (наприклад, з тестового контейнера) без повторного підключення.
+    /// Схема за замовчуванням — `public`, змінити її можна через `with_schema`.
+    pub fn from_pool(pool: Pool<Postgres>) -> Self {
+        Self { pool, schema: "public".to_string() }
+    }
+
+    /// Змінює схему Postgres, з якою працює клієнт
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = schema.into();
+        self
     }
 
     pub fn pool(&self) -> &Pool<Postgres> {
         &self.pool
     }
 
+    /// Повне ім'я таблиці з урахуванням схеми: `"schema"."table"`
+    pub fn qualified(&self, table_name: &str) -> String {
+        format!("\"{}\".\"{}\"", self.schema, table_name)
+    }
+
+    /// Перевіряє, чи містить таблиця хоча б один рядок
+    pub async fn table_has_rows(&self, conn: &mut PgConnection, table_name: &str) -> AppResult<bool> {
+        let sql = format!("SELECT EXISTS(SELECT 1 FROM {})", self.qualified(table_name));
+        let exists: bool = sqlx::query_scalar(&sql).fetch_one(conn).await?;
+        Ok(exists)
+    }
+
+    /// Рахує кількість рядків у таблиці через передане з'єднання, тож враховує і його незакомічені зміни
+    pub async fn count_rows(&self, conn: &mut PgConnection, table_name: &str) -> AppResult<i64> {
+        let sql = format!("SELECT COUNT(*) FROM {}", self.qualified(table_name));
+        let count: i64 = sqlx::query_scalar(&sql).fetch_one(conn).await?;
+        Ok(count)
+    }
+
+    /// Найбільше значення цілочисельної колонки (`None` для порожньої таблиці)
+    pub async fn max_integer(&self, conn: &mut PgConnection, table_name: &str, column: &str) -> AppResult<Option<i64>> {
+        let sql = format!("SELECT MAX(\"{}\")::int8 FROM {}", column, self.qualified(table_name));
+        let max: Option<i64> = sqlx::query_scalar(&sql).fetch_one(conn).await?;
+        Ok(max)
+    }
+
+    /// Перші `limit` значень PK колонки, приведені до `cast` (як у `RETURNING` під час вставки), у порядку ключа
+    pub async fn fetch_primary_keys(&self, conn: &mut PgConnection, table_name: &str, pk_column: &str, cast: &str, limit: u32) -> AppResult<Vec<serde_json::Value>> {
+        let sql = format!("SELECT to_jsonb(\"{0}\"::{1}) FROM {2} ORDER BY \"{0}\" LIMIT $1", pk_column, cast, self.qualified(table_name));
+        let keys: Vec<serde_json::Value> = sqlx::query_scalar(&sql).bind(i64::from(limit)).fetch_all(conn).await?;
+        Ok(keys)
+    }
+
+    /// Значення єдиної колонки результату запиту (для генератора `from_query`) з їхніми JSON-типами.
+    /// Запит виконується в транзакції лише для читання, тож не може змінити дані
+    pub async fn fetch_values(&self, query: &str) -> AppResult<Vec<serde_json::Value>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION READ ONLY").execute(&mut *tx).await?;
+        let sql = format!("SELECT to_jsonb(q) FROM ({}) AS q", query.trim().trim_end_matches(';'));
+        let rows: Vec<serde_json::Value> = sqlx::query_scalar(&sql).fetch_all(&mut *tx).await?;
+        tx.rollback().await?;
+
+        rows.into_iter()
+            .map(|row| match row {
+                serde_json::Value::Object(columns) if columns.len() == 1 => Ok(columns.into_iter().next().map(|(_, v)| v).unwrap_or_default()),
+                _ => Err(AppError::Custom(format!("Запит для `from_query` має повертати рівно одну колонку: {}", query))),
+            })
+            .collect()
+    }
+
+    /// Оновлює статистику планувальника для таблиці. VACUUM не працює в транзакції, тому йде через пул
+    pub async fn analyze(&self, table_name: &str, vacuum: bool) -> AppResult<()> {
+        let command = if vacuum { "VACUUM ANALYZE" } else { "ANALYZE" };
+        let sql = format!("{} {}", command, self.qualified(table_name));
+        sqlx::query(&sql).execute(&self.pool).await?;
+        Ok(())
+    }
+
     /// Отримує повну схему бази даних (таблиці, колонки, зв'язки)
     pub async fn fetch_schema(&self) -> AppResult<DbSchema> {
         // Отримуємо всі таблиці
         let table_rows = sqlx::query(
-            "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public' AND table_type = 'BASE TABLE'",
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = $1 AND table_type = 'BASE TABLE'",
         )
+        .bind(&self.schema)
         .fetch_all(&self.pool)
         .await?;
 
@@ -65,17 +269,25 @@ impl DbClient {
             let table_name: String = row.get("table_name");
             let columns = self.fetch_columns_for_table(&table_name).await?;
             let primary_key_column = self.fetch_primary_key(&table_name).await?;
+            let mut check_constraints = self.fetch_check_constraints(&table_name).await?;
+            for column in &columns {
+                if let Some((domain_schema, domain_name)) = &column.domain {
+                    check_constraints.extend(self.fetch_domain_checks(domain_schema, domain_name, &column.name).await?);
+                }
+            }
             tables.insert(
                 table_name.clone(),
                 TableSchema {
                     name: table_name,
                     columns,
                     primary_key_column,
+                    check_constraints,
                 },
             );
         }
 
         let foreign_keys = self.fetch_foreign_keys().await?;
+        debug!("Схему прочитано: {} таблиць, {} зовнішніх ключів", tables.len(), foreign_keys.len());
 
         Ok(DbSchema {
             tables,
@@ -87,21 +299,60 @@ impl DbClient {
     async fn fetch_columns_for_table(&self, table_name: &str) -> AppResult<Vec<ColumnSchema>> {
         // ВИПРАВЛЕНО: Додаємо `column_default` до запиту
         let rows = sqlx::query(
-            "SELECT column_name, data_type, is_nullable, column_default 
+            "SELECT column_name, data_type, is_nullable, column_default, udt_schema, udt_name,
+                    character_maximum_length::int4 AS max_length, domain_schema, domain_name,
+                    is_identity = 'YES' OR is_generated = 'ALWAYS' AS db_generated,
+                    identity_generation = 'ALWAYS' OR is_generated = 'ALWAYS' AS db_generated_always
              FROM information_schema.columns 
-             WHERE table_name = $1 AND table_schema = 'public'"
+             WHERE table_name = $1 AND table_schema = $2"
         )
         .bind(table_name)
+        .bind(&self.schema)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(|row| ColumnSchema {
-            name: row.get("column_name"),
-            data_type: row.get("data_type"),
-            is_nullable: row.get::<String, _>("is_nullable") == "YES",
-            // ВИПРАВЛЕНО: Читаємо значення за замовчуванням
-            column_default: row.get("column_default"),
-        }).collect())
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut column = ColumnSchema {
+                name: row.get("column_name"),
+                data_type: row.get("data_type"),
+                is_nullable: row.get::<String, _>("is_nullable") == "YES",
+                // ВИПРАВЛЕНО: Читаємо значення за замовчуванням
+                column_default: row.get("column_default"),
+                udt_schema: row.get("udt_schema"),
+                udt_name: row.get("udt_name"),
+                enum_labels: Vec::new(),
+                max_length: row.get("max_length"),
+                domain: row
+                    .get::<Option<String>, _>("domain_name")
+                    .map(|name| (row.get::<Option<String>, _>("domain_schema").unwrap_or_default(), name)),
+                db_generated: row.get("db_generated"),
+                db_generated_always: row.get::<Option<bool>, _>("db_generated_always").unwrap_or(false),
+            };
+            if column.data_type == "USER-DEFINED" {
+                column.enum_labels = self.fetch_enum_labels(&column.udt_schema, &column.udt_name).await?;
+            }
+            columns.push(column);
+        }
+        Ok(columns)
+    }
+
+    /// Отримує значення enum типу в порядку їх оголошення (порожній список, якщо тип не є enum)
+    async fn fetch_enum_labels(&self, type_schema: &str, type_name: &str) -> AppResult<Vec<String>> {
+        let rows = sqlx::query(r#"
+            SELECT e.enumlabel
+            FROM   pg_enum e
+            JOIN   pg_type t ON t.oid = e.enumtypid
+            JOIN   pg_namespace n ON n.oid = t.typnamespace
+            WHERE  t.typname = $1 AND n.nspname = $2
+            ORDER  BY e.enumsortorder
+        "#)
+        .bind(type_name)
+        .bind(type_schema)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.get("enumlabel")).collect())
     }
     
     /// Отримує первинний ключ таблиці (підтримуємо тільки один для простоти)
@@ -114,13 +365,65 @@ impl DbClient {
             WHERE  i.indrelid = $1::regclass
             AND    i.indisprimary;
         "#)
-        .bind(table_name)
+        .bind(self.qualified(table_name))
         .fetch_optional(&self.pool)
         .await?;
         
         Ok(row.map(|r| r.get("attname")))
     }
     
+    /// Отримує CHECK обмеження таблиці разом з колонками, яких вони стосуються
+    async fn fetch_check_constraints(&self, table_name: &str) -> AppResult<Vec<CheckConstraint>> {
+        let rows = sqlx::query(r#"
+            SELECT c.conname::text AS name,
+                   pg_get_constraintdef(c.oid) AS definition,
+                   ARRAY(
+                       SELECT a.attname::text
+                       FROM   pg_attribute a
+                       WHERE  a.attrelid = c.conrelid AND a.attnum = ANY(c.conkey)
+                   ) AS columns
+            FROM   pg_constraint c
+            WHERE  c.conrelid = $1::regclass AND c.contype = 'c'
+            ORDER  BY c.conname
+        "#)
+        .bind(self.qualified(table_name))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| CheckConstraint {
+            name: row.get("name"),
+            definition: row.get("definition"),
+            columns: row.get("columns"),
+        }).collect())
+    }
+
+    /// CHECK обмеження домену у формі обмеження колонки: `VALUE` замінюється на ім'я колонки,
+    /// тож `allowed_values` та `numeric_bounds` працюють з ними так само, як з CHECK таблиці
+    async fn fetch_domain_checks(&self, domain_schema: &str, domain_name: &str, column: &str) -> AppResult<Vec<CheckConstraint>> {
+        let rows = sqlx::query(r#"
+            SELECT c.conname::text AS name, pg_get_constraintdef(c.oid) AS definition
+            FROM   pg_constraint c
+                   JOIN pg_type t ON t.oid = c.contypid
+                   JOIN pg_namespace n ON n.oid = t.typnamespace
+            WHERE  n.nspname = $1 AND t.typname = $2 AND c.contype = 'c'
+            ORDER  BY c.conname
+        "#)
+        .bind(domain_schema)
+        .bind(domain_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let value_re = Regex::new(r"\bVALUE\b").unwrap();
+        Ok(rows.into_iter().map(|row| {
+            let definition: String = row.get("definition");
+            CheckConstraint {
+                name: row.get("name"),
+                definition: value_re.replace_all(&definition, column).into_owned(),
+                columns: vec![column.to_string()],
+            }
+        }).collect())
+    }
+
     /// Отримує всі зовнішні ключі в схемі
     async fn fetch_foreign_keys(&self) -> AppResult<Vec<ForeignKey>> {
         let rows = sqlx::query(r#"
@@ -137,8 +440,9 @@ impl DbClient {
                 JOIN information_schema.constraint_column_usage AS ccu
                   ON ccu.constraint_name = tc.constraint_name
                   AND ccu.table_schema = tc.table_schema
-            WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = 'public';
+            WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = $1;
         "#)
+        .bind(&self.schema)
         .fetch_all(&self.pool)
         .await?;
 
@@ -1,21 +1,181 @@
 // src/entity_generator.rs
 use crate::error::{AppError, AppResult};
-use crate::gemini_analyzer::FieldTemplate;
-use fake::{faker, Fake};
-use rand::distributions::Alphanumeric;
-use rand::Rng;
-use serde_json::{json, Value};
-use std::collections::HashMap;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use crate::gemini_analyzer::{EntityTemplate, FieldTemplate};
+use rand::rngs::StdRng;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, OnceLock};
+
+mod builtins;
 
 pub type DataPools = HashMap<String, Vec<Value>>;
 pub type GeneratedEntity = HashMap<String, Value>;
 
-pub struct EntityGenerator {}
+/// Дані, доступні генератору під час генерації одного значення
+pub struct GeneratorContext<'a> {
+    pub column: &'a str,
+    pub params: &'a HashMap<String, Value>,
+    /// Уже згенеровані поля цього рядка
+    pub row: &'a GeneratedEntity,
+    /// Порядковий номер рядка в таблиці (з нуля)
+    pub row_index: u64,
+    pub pools: &'a DataPools,
+    /// Генератор випадкових чисел таблиці: з `generation.seed` значення відтворювані
+    pub rng: &'a mut StdRng,
+    /// Ключі вже заповнених таблиць для `fk`
+    all_pks: &'a DataPools,
+    state: Slot<'a, TableState>,
+    generator: &'a EntityGenerator,
+    /// Точки `geo_point` цього рядка: окремі колонки latitude/longitude беруть координати однієї точки
+    geo_points: Slot<'a, HashMap<String, (f64, f64)>>,
+    /// `Value::Null` вставляється як явний NULL, а не пропускає колонку (див. `null`)
+    explicit_null: bool,
+}
+
+impl<'a> GeneratorContext<'a> {
+    /// Контекст для перевірки власного генератора окремо від сідера: порожній стан таблиці, `row_index` 0,
+    /// без ключів батьківських таблиць (тож `fk` їх не знайде) та з генератором мови "en" для вкладених генераторів
+    pub fn for_test(
+        column: &'a str,
+        params: &'a HashMap<String, Value>,
+        row: &'a GeneratedEntity,
+        pools: &'a DataPools,
+        rng: &'a mut StdRng,
+    ) -> Self {
+        static GENERATOR: OnceLock<EntityGenerator> = OnceLock::new();
+        static NO_KEYS: OnceLock<DataPools> = OnceLock::new();
+        Self {
+            column,
+            params,
+            row,
+            row_index: 0,
+            pools,
+            rng,
+            all_pks: NO_KEYS.get_or_init(DataPools::new),
+            state: Slot::Owned(TableState::default()),
+            generator: GENERATOR.get_or_init(|| EntityGenerator::new("en")),
+            geo_points: Slot::Owned(HashMap::new()),
+            explicit_null: false,
+        }
+    }
+
+    /// Явний SQL NULL: колонка потрапляє в INSERT зі значенням NULL, а не отримує значення за замовчуванням з БД,
+    /// як при поверненні `Value::Null` (див. `constant`, `fk` з `null_chance`)
+    pub fn null(&mut self) -> Value {
+        self.explicit_null = true;
+        Value::Null
+    }
+
+    /// Чи повернув генератор явний NULL через `null`
+    pub fn is_explicit_null(&self) -> bool {
+        self.explicit_null
+    }
+}
+
+/// Стан, який контекст позичає в `generate_entity` або, у `GeneratorContext::for_test`, має сам
+enum Slot<'a, T> {
+    Borrowed(&'a mut T),
+    Owned(T),
+}
+
+impl<T> Deref for Slot<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            Slot::Borrowed(value) => value,
+            Slot::Owned(value) => value,
+        }
+    }
+}
+
+impl<T> DerefMut for Slot<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            Slot::Borrowed(value) => value,
+            Slot::Owned(value) => value,
+        }
+    }
+}
+
+/// Генератор значення колонки, вбудований чи користувацький (наприклад, `invoice_number`).
+/// `Value::Null` означає, що колонка не заповнюється і отримує значення за замовчуванням з БД;
+/// явний NULL повертає `GeneratorContext::null`
+pub trait GeneratorFn: Fn(&mut GeneratorContext) -> AppResult<Value> + Send + Sync {}
+
+impl<F> GeneratorFn for F where F: Fn(&mut GeneratorContext) -> AppResult<Value> + Send + Sync {}
+
+/// Генератори за назвою: вбудовані та зареєстровані користувачем
+pub type GeneratorRegistry = HashMap<String, Arc<dyn GeneratorFn>>;
+
+/// Стан генерації в межах однієї таблиці, що зберігається між рядками
+#[derive(Debug, Default)]
+pub struct TableState {
+    /// Порядковий номер рядка, що генерується (з нуля); при повторній генерації рядка не змінюється
+    pub row_index: u64,
+    /// Уже видані значення для колонок з вимогою унікальності
+    used_values: HashMap<String, HashSet<String>>,
+    /// Індекси ще не використаних батьківських PK для `fk` з `unique: true`, у випадковому порядку
+    unused_parents: HashMap<String, Vec<usize>>,
+    /// Останній виданий батьківський індекс для `fk` з `unique: true` та рядок, якому його видано:
+    /// якщо рядок генерується повторно, індекс повертається в `unused_parents`
+    chosen_parents: HashMap<String, (u64, usize)>,
+    /// Початкові значення `sequence` для колонок, заданих ззовні (режим `generation.append`)
+    pub sequence_starts: HashMap<String, i64>,
+    /// Хеші відомого пароля для `password_hash`: хешування повільне за задумом, тож рахуємо його раз на колонку
+    password_hashes: HashMap<String, String>,
+}
+
+pub struct EntityGenerator {
+    /// Спеціальні токени шаблону: {random_digits:N}, {uuid}, {random_int:min-max}
+    template_tokens: Regex,
+    /// Мова для `words`/`sentence`/`company`/`job_title`, якщо в параметрах поля не вказано `locale`
+    default_locale: String,
+    /// Вбудовані генератори та зареєстровані через `register` (які можуть замінити вбудовані з тією ж назвою)
+    generators: GeneratorRegistry,
+    /// Уже видані значення для `unique` зі `scope: "global:<name>"` (ключ — назва області).
+    /// Сідер створює генератор один раз на запуск, тож області спільні для всіх його таблиць
+    unique_scopes: Mutex<HashMap<String, HashSet<String>>>,
+}
 
 impl EntityGenerator {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            template_tokens: Regex::new(r"\{(?:random_digits:(\d+)|(uuid)|random_int:(-?\d+)-(-?\d+))\}")
+                .expect("регулярний вираз для токенів шаблону має бути валідним"),
+            default_locale: default_locale.to_string(),
+            generators: builtins::registry(),
+            unique_scopes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Викликає `f` з множиною вже виданих значень поля: спільною для всього запуску для `scope: "global:<name>"`,
+    /// інакше — окремою для колонки цієї таблиці
+    fn with_used_values<T>(
+        &self,
+        column: &str,
+        params: &HashMap<String, Value>,
+        state: &mut TableState,
+        f: impl FnOnce(&mut HashSet<String>) -> T,
+    ) -> AppResult<T> {
+        match params.get("scope").and_then(|v| v.as_str()) {
+            None => Ok(f(state.used_values.entry(column.to_string()).or_default())),
+            Some(scope) => {
+                let name = scope.strip_prefix("global:").filter(|n| !n.is_empty()).ok_or_else(|| AppError::Custom(format!(
+                    "Невірний `scope` '{}' для '{}': очікується \"global:<назва>\"", scope, column
+                )))?;
+                let mut scopes = self.unique_scopes.lock().unwrap();
+                Ok(f(scopes.entry(name.to_string()).or_default()))
+            }
+        }
+    }
+
+    /// Реєструє генератор, доступний у плані та `column_overrides` під назвою `name`.
+    /// Генератор з назвою вбудованого (наприклад, `words`) замінює його
+    pub fn register(&mut self, name: &str, generator: Arc<dyn GeneratorFn>) {
+        self.generators.insert(name.to_string(), generator);
     }
 
     pub fn generate_entity(
@@ -23,116 +183,178 @@ impl EntityGenerator {
         fields: &[FieldTemplate],
         pools: &DataPools,
         all_pks: &DataPools,
+        state: &mut TableState,
+        rng: &mut StdRng,
     ) -> AppResult<GeneratedEntity> {
         let mut entity = GeneratedEntity::new();
-        let mut rng = rand::thread_rng();
+        let mut geo_points: HashMap<String, (f64, f64)> = HashMap::new();
 
         for field in fields {
-            let value = match field.generator.as_str() {
-                "pk_hash" => {
-                    let length = field.params.get("length").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-                    let hash: String = (&mut rng).sample_iter(&Alphanumeric).take(length).map(char::from).collect();
-                    json!(hash)
-                }
-                "from_pool" => {
-                    let pool_name = field.params.get("pool_name").and_then(|v| v.as_str()).ok_or_else(|| AppError::Custom("`pool_name` не вказано для генератора `from_pool`".to_string()))?;
-                    let pool = pools.get(pool_name).ok_or_else(|| AppError::Custom(format!("Пул даних '{}' не знайдено", pool_name)))?;
-                    if pool.is_empty() {
-                       json!(Value::Null) // Повертаємо null, якщо пул порожній
-                    } else {
-                        pool[rng.gen_range(0..pool.len())].clone()
-                    }
-                }
-                "template" => {
-                    let format = field.params.get("format").and_then(|v| v.as_str()).ok_or_else(|| AppError::Custom("`format` не вказано для `template`".to_string()))?;
-                    let mut result = format.to_string();
-                    for (key, val) in &entity {
-                        // Для числових значень теж робимо заміну
-                        let val_str = match val {
-                            Value::String(s) => s.clone(),
-                            Value::Number(n) => n.to_string(),
-                            _ => "".to_string(),
-                        };
-                        if !val_str.is_empty() {
-                           result = result.replace(&format!("{{{}}}", key), &val_str);
-                        }
-                    }
-                    if result.contains("{random_digits:") {
-                         let num: u32 = rng.gen_range(1000..9999);
-                         result = result.replace("{random_digits:4}", &format!("{:04}", num));
-                    }
-                    json!(result)
-                }
-                "fk" => {
-                    let parent_table = field.params.get("references").and_then(|v| v.as_str())
-                        .ok_or_else(|| AppError::Custom("`references` не вказано для `fk`".to_string()))?;
-                    
-                    if let Some(pk_pool) = all_pks.get(parent_table) {
-                        if pk_pool.is_empty() {
-                            json!(Value::Null)
-                        } else {
-                            pk_pool[rng.gen_range(0..pk_pool.len())].clone()
-                        }
-                    } else {
-                        // Якщо пулу взагалі немає, це помилка залежностей.
-                        // Це може статися, якщо батьківська таблиця не була в плані.
-                        return Err(AppError::DependencyNotFound(parent_table.to_string()));
-                    }
-                }
-                "words" => {
-                    let min = field.params.get("min").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
-                    let max = field.params.get("max").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
-                    let text: Vec<String> = faker::lorem::en::Words(min..max).fake();
-                    json!(text.join(" "))
-                }
-                "number_range" => {
-                    let mut min = field.params.get("min").and_then(|v| v.as_i64()).unwrap_or(0);
-                    let mut max = field.params.get("max").and_then(|v| v.as_i64()).unwrap_or(100);
-                    if min > max {
-                        // Якщо Gemini переплутав min та max, міняємо їх місцями
-                        std::mem::swap(&mut min, &mut max);
-                    }
-                    json!(rng.gen_range(min..=max)) // ..= включає max, тому min == max є валідним
-                }
-                "boolean" => {
-                    let true_chance = field.params.get("true_chance").and_then(|v| v.as_f64()).unwrap_or(0.5);
-                    json!(rng.gen_bool(true_chance))
-                }
-                "sentence" => {
-                    let min = field.params.get("min").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
-                    let max = field.params.get("max").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
-                    let text: String = faker::lorem::en::Sentence(min..max).fake();
-                    json!(text)
-                }
-                "datetime_range" => {
-                    let start_str = field.params.get("start").and_then(|v| v.as_str()).unwrap_or("2020-01-01");
-                    let end_str = field.params.get("end").and_then(|v| v.as_str()).unwrap_or("2024-01-01");
-
-                    let start_dt = NaiveDateTime::parse_from_str(&format!("{} 00:00:00", start_str), "%Y-%m-%d %H:%M:%S")
-                        .map(|ndt| ndt.and_utc())
-                        .unwrap_or_else(|_| Utc::now());
-
-                    let end_dt = NaiveDateTime::parse_from_str(&format!("{} 23:59:59", end_str), "%Y-%m-%d %H:%M:%S")
-                        .map(|ndt| ndt.and_utc())
-                        .unwrap_or_else(|_| Utc::now());
-
-                    let start_ts = start_dt.timestamp();
-                    let end_ts = end_dt.timestamp();
-
-                    if start_ts >= end_ts {
-                        json!(start_dt.to_rfc3339())
-                    } else {
-                        let random_ts = rng.gen_range(start_ts..=end_ts);
-                        let random_dt = DateTime::from_timestamp(random_ts, 0).unwrap_or_else(|| Utc::now());
-                        json!(random_dt.to_rfc3339())
-                    }
-                }
-                _ => return Err(AppError::UnknownGenerator(field.generator.clone())),
+            let generator = self.generators.get(&field.generator).ok_or_else(|| AppError::UnknownGenerator(field.generator.clone()))?;
+            let mut context = GeneratorContext {
+                column: &field.column_name,
+                params: &field.params,
+                row: &entity,
+                row_index: state.row_index,
+                pools,
+                rng: &mut *rng,
+                all_pks,
+                state: Slot::Borrowed(&mut *state),
+                generator: self,
+                geo_points: Slot::Borrowed(&mut geo_points),
+                explicit_null: false,
             };
-            if !value.is_null() {
+            let value = generator(&mut context)?;
+            let explicit_null = context.is_explicit_null();
+            if !value.is_null() || explicit_null {
                 entity.insert(field.column_name.clone(), value);
             }
         }
         Ok(entity)
     }
-}
\ No newline at end of file
+}
+
+/// Ключ у `DataPools`, під яким сідер зберігає результат запиту `from_query`
+pub fn from_query_key(params: &HashMap<String, Value>) -> Option<String> {
+    if let Some(query) = params.get("query").and_then(|v| v.as_str()) {
+        return Some(format!("from_query:{}", query.trim()));
+    }
+    let table = params.get("table").and_then(|v| v.as_str())?;
+    let column = params.get("column").and_then(|v| v.as_str())?;
+    Some(format!("from_query:{}.{}", table, column))
+}
+
+/// Поля, значення яких використовують інші поля того ж шаблону (`template`, `expression`, `slug`, `boolean` з `when`)
+pub fn referenced_fields(template: &EntityTemplate) -> HashSet<String> {
+    let names: HashSet<&str> = template.fields.iter().map(|f| f.column_name.as_str()).collect();
+    template.fields.iter().flat_map(|f| field_dependencies(f, &names)).collect()
+}
+
+/// Упорядковує поля сутності так, щоб поле генерувалося після всіх полів, на які воно посилається
+/// (наприклад, `{first_name}` у `template`). Порядок оголошення зберігається, де це можливо.
+pub fn order_fields(template: &EntityTemplate) -> AppResult<Vec<FieldTemplate>> {
+    let names: HashSet<&str> = template.fields.iter().map(|f| f.column_name.as_str()).collect();
+    let dependencies: Vec<Vec<String>> = template.fields.iter().map(|f| field_dependencies(f, &names)).collect();
+
+    let mut ordered: Vec<FieldTemplate> = Vec::with_capacity(template.fields.len());
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut remaining: Vec<usize> = (0..template.fields.len()).collect();
+
+    while !remaining.is_empty() {
+        let ready = remaining
+            .iter()
+            .position(|&i| dependencies[i].iter().all(|d| placed.contains(d.as_str())));
+
+        let Some(position) = ready else {
+            let cycle: Vec<&str> = remaining.iter().map(|&i| template.fields[i].column_name.as_str()).collect();
+            return Err(AppError::FieldDependencyCycle(template.entity_name.clone(), cycle.join(", ")));
+        };
+
+        let index = remaining.remove(position);
+        placed.insert(template.fields[index].column_name.as_str());
+        ordered.push(template.fields[index].clone());
+    }
+    Ok(ordered)
+}
+
+/// Повертає імена інших полів цієї ж сутності, від яких залежить значення поля
+fn field_dependencies(field: &FieldTemplate, names: &HashSet<&str>) -> Vec<String> {
+    let placeholder = Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("регулярний вираз для плейсхолдерів має бути валідним");
+
+    let mut deps = Vec::new();
+    match field.generator.as_str() {
+        "template" => {
+            if let Some(format) = field.params.get("format").and_then(|v| v.as_str()) {
+                deps.extend(placeholder.captures_iter(format).map(|c| c[1].to_string()));
+            }
+        }
+        "expression" => {
+            if let Some(formula) = field.params.get("formula").and_then(|v| v.as_str()) {
+                let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("регулярний вираз для ідентифікаторів має бути валідним");
+                deps.extend(identifier.find_iter(formula).map(|m| m.as_str().to_string()));
+            }
+        }
+        "slug" => {
+            if let Some(from) = field.params.get("from").and_then(|v| v.as_str()) {
+                deps.push(from.to_string());
+            }
+        }
+        "boolean" => {
+            if let Some(other) = field.params.get("when").and_then(|w| w.get("field")).and_then(|v| v.as_str()) {
+                deps.push(other.to_string());
+            }
+        }
+        _ => {}
+    }
+
+    deps.retain(|d| d != &field.column_name && names.contains(d.as_str()));
+    deps.sort_unstable();
+    deps.dedup();
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    /// Генерує `rows` значень одного поля з фіксованим зерном
+    pub(super) fn generate_column(generator: &EntityGenerator, name: &str, params: Value, rows: u64) -> Vec<Option<Value>> {
+        let field = FieldTemplate {
+            column_name: "value".to_string(),
+            generator: name.to_string(),
+            params: serde_json::from_value(params).expect("параметри генератора мають бути об'єктом"),
+        };
+        let mut state = TableState::default();
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..rows)
+            .map(|row_index| {
+                state.row_index = row_index;
+                let mut entity = generator
+                    .generate_entity(std::slice::from_ref(&field), &DataPools::new(), &DataPools::new(), &mut state, &mut rng)
+                    .expect("генерація значення");
+                entity.remove("value")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builtins_are_registered() {
+        let generator = EntityGenerator::new("en");
+        let values = generate_column(&generator, "sequence", json!({"start": 5, "step": 2}), 3);
+        assert_eq!(values, vec![Some(json!(5)), Some(json!(7)), Some(json!(9))]);
+    }
+
+    #[test]
+    fn register_replaces_builtin() {
+        let mut generator = EntityGenerator::new("en");
+        generator.register("words", Arc::new(|ctx: &mut GeneratorContext| Ok(Value::String(format!("row-{}", ctx.row_index)))));
+        let values = generate_column(&generator, "words", json!({}), 2);
+        assert_eq!(values, vec![Some(json!("row-0")), Some(json!("row-1"))]);
+    }
+
+    #[test]
+    fn unknown_generator_is_an_error() {
+        let generator = EntityGenerator::new("en");
+        let field = FieldTemplate { column_name: "value".to_string(), generator: "no_such".to_string(), params: HashMap::new() };
+        let result = generator.generate_entity(&[field], &DataPools::new(), &DataPools::new(), &mut TableState::default(), &mut StdRng::seed_from_u64(1));
+        assert!(matches!(result, Err(AppError::UnknownGenerator(name)) if name == "no_such"));
+    }
+
+    #[test]
+    fn explicit_null_is_kept() {
+        let generator = EntityGenerator::new("en");
+        assert_eq!(generate_column(&generator, "constant", json!({"value": null}), 1), vec![Some(Value::Null)]);
+        // Порожній пул не заповнює колонку зовсім
+        let field = FieldTemplate {
+            column_name: "value".to_string(),
+            generator: "choice".to_string(),
+            params: HashMap::from([("options".to_string(), json!([]))]),
+        };
+        let entity = generator
+            .generate_entity(&[field], &DataPools::new(), &DataPools::new(), &mut TableState::default(), &mut StdRng::seed_from_u64(1))
+            .unwrap();
+        assert!(!entity.contains_key("value"));
+    }
+}
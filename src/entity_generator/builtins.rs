@@ -0,0 +1,771 @@
+// src/entity_generator/builtins.rs
+//! Вбудовані генератори. Кожен — звичайний елемент реєстру, тож його можна замінити через `register`.
+use super::{from_query_key, EntityGenerator, GeneratedEntity, GeneratorContext, GeneratorRegistry};
+use crate::error::{AppError, AppResult};
+use crate::gemini_analyzer::FieldTemplate;
+use crate::iso_codes;
+use crate::lorem;
+use base64::prelude::*;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use evalexpr::{eval_with_context, ContextWithMutableVariables, EvalexprError, HashMapContext, Value as EvalValue};
+use fake::uuid::UUIDv4;
+use fake::{faker, Fake};
+use rand::distributions::{Alphanumeric, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_distr::{Distribution, Zipf};
+use regex::Captures;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+type Params = HashMap<String, Value>;
+type Builtin = fn(&mut GeneratorContext) -> AppResult<Value>;
+
+/// Реєстр з усіма вбудованими генераторами, з якого починає кожен `EntityGenerator`
+pub fn registry() -> GeneratorRegistry {
+    let builtins: [(&str, Builtin); 28] = [
+        ("pk_hash", pk_hash),
+        ("from_query", from_query),
+        ("from_pool", from_pool),
+        ("constant", constant),
+        ("expression", expression),
+        ("slug", slug),
+        ("company", company),
+        ("job_title", job_title),
+        ("url", url),
+        ("choice", choice),
+        ("template", template),
+        ("fk", fk),
+        ("geo_point", geo_point),
+        ("words", words),
+        ("sequence", sequence),
+        ("number_range", number_range),
+        ("boolean", boolean),
+        ("password_hash", password_hash),
+        ("currency_code", currency_code),
+        ("country_code", country_code),
+        ("hex_color", hex_color),
+        ("json_array", json_array),
+        ("bytea", bytea),
+        ("sentence", sentence),
+        ("paragraphs", paragraphs),
+        ("time_range", time_range),
+        ("interval", interval),
+        ("datetime_range", datetime_range),
+    ];
+    builtins.into_iter().map(|(name, generator)| (name.to_string(), Arc::new(generator) as _)).collect()
+}
+
+fn param_str<'p>(params: &'p Params, name: &str) -> Option<&'p str> {
+    params.get(name).and_then(|v| v.as_str())
+}
+
+fn param_u64(params: &Params, name: &str) -> Option<u64> {
+    params.get(name).and_then(|v| v.as_u64())
+}
+
+fn param_bool(params: &Params, name: &str) -> bool {
+    params.get(name).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// Мова поля: `locale` з параметрів або мова генерації
+fn locale<'p>(params: &'p Params, generator: &'p EntityGenerator) -> &'p str {
+    params.get("locale").and_then(|v| v.as_str()).unwrap_or(&generator.default_locale)
+}
+
+fn pk_hash(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let length = param_u64(ctx.params, "length").unwrap_or(20) as usize;
+    let prefix = param_str(ctx.params, "prefix").unwrap_or("");
+    // Префікс не входить у `length`: "usr_" + 8 випадкових символів
+    let hash: String = match param_str(ctx.params, "charset").unwrap_or("alnum") {
+        "alnum" => (&mut *ctx.rng).sample_iter(&Alphanumeric).take(length).map(char::from).collect(),
+        "hex" => (0..length).map(|_| HEX_CHARS[ctx.rng.gen_range(0..HEX_CHARS.len())] as char).collect(),
+        "alpha" => (0..length).map(|_| ALPHA_CHARS[ctx.rng.gen_range(0..ALPHA_CHARS.len())] as char).collect(),
+        other => return Err(AppError::Custom(format!(
+            "Невідомий `charset` '{}' для `pk_hash` (очікується alnum, hex або alpha)", other
+        ))),
+    };
+    Ok(json!(format!("{}{}", prefix, hash)))
+}
+
+fn from_query(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    // Значення завантажує сідер до початку заповнення (див. `from_query_key`)
+    let key = from_query_key(ctx.params).ok_or_else(|| AppError::Custom(format!(
+        "`from_query` для '{}' потребує `query` або `table` разом із `column`", ctx.column
+    )))?;
+    let values = ctx.pools.get(&key).ok_or_else(|| AppError::Custom(format!("Значення для `from_query` '{}' не завантажено", ctx.column)))?;
+    if values.is_empty() {
+        Ok(Value::Null)
+    } else {
+        Ok(values[ctx.rng.gen_range(0..values.len())].clone())
+    }
+}
+
+fn from_pool(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let pool_name = param_str(ctx.params, "pool_name").ok_or_else(|| AppError::Custom("`pool_name` не вказано для генератора `from_pool`".to_string()))?;
+    let pool = ctx.pools.get(pool_name).ok_or_else(|| AppError::Custom(format!("Пул даних '{}' не знайдено", pool_name)))?;
+    if pool.is_empty() {
+        Ok(Value::Null) // Повертаємо null, якщо пул порожній
+    } else {
+        Ok(pool[ctx.rng.gen_range(0..pool.len())].clone())
+    }
+}
+
+fn constant(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let value = ctx.params.get("value").cloned().ok_or_else(|| AppError::Custom("`value` не вказано для `constant`".to_string()))?;
+    if value.is_null() {
+        return Ok(ctx.null());
+    }
+    Ok(value)
+}
+
+fn expression(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let formula = param_str(ctx.params, "formula").ok_or_else(|| AppError::Custom("`formula` не вказано для `expression`".to_string()))?;
+    evaluate_formula(formula, ctx.row)
+        .map_err(|e| AppError::Custom(format!("Помилка обчислення виразу '{}' для поля '{}': {}", formula, ctx.column, e)))
+}
+
+fn slug(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let from = param_str(ctx.params, "from").ok_or_else(|| AppError::Custom("`from` не вказано для `slug`".to_string()))?;
+    let Some(source) = ctx.row.get(from).filter(|v| !v.is_null()) else {
+        return Ok(Value::Null);
+    };
+    let base = slugify(source.as_str().map(str::to_string).unwrap_or_else(|| source.to_string()).as_str());
+    if !param_bool(ctx.params, "unique") {
+        return Ok(json!(base));
+    }
+    let rng = &mut *ctx.rng;
+    let slug = ctx.generator.with_used_values(ctx.column, ctx.params, &mut ctx.state, |used| {
+        let mut slug = base.clone();
+        // Суфікс із 6 символів дає ~2 млрд варіантів, тож колізії тут рідкісні
+        while slug == base || used.contains(&slug) {
+            let suffix: String = (&mut *rng).sample_iter(&Alphanumeric).take(6).map(|c| char::from(c).to_ascii_lowercase()).collect();
+            slug = if base.is_empty() { suffix } else { format!("{}-{}", base, suffix) };
+        }
+        used.insert(slug.clone());
+        slug
+    })?;
+    Ok(json!(slug))
+}
+
+fn company(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let locale = locale(ctx.params, ctx.generator);
+    Ok(json!(lorem::company(ctx.rng, locale)))
+}
+
+fn job_title(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let locale = locale(ctx.params, ctx.generator);
+    Ok(json!(lorem::job_title(ctx.rng, locale)))
+}
+
+fn url(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let scheme = param_str(ctx.params, "scheme").unwrap_or("https");
+    let with_path = ctx.params.get("with_path").and_then(|v| v.as_bool()).unwrap_or(true);
+    let host: String = faker::lorem::en::Word().fake_with_rng(ctx.rng);
+    let suffix: String = faker::internet::en::DomainSuffix().fake_with_rng(ctx.rng);
+    let mut url = format!("{}://{}.{}", scheme, host, suffix);
+    if with_path {
+        let segments: Vec<String> = faker::lorem::en::Words(1..4).fake_with_rng(ctx.rng);
+        url.push('/');
+        url.push_str(&segments.join("/"));
+    }
+    Ok(json!(url))
+}
+
+fn choice(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let options = ctx.params.get("options").and_then(|v| v.as_array()).ok_or_else(|| AppError::Custom("`options` не вказано для `choice`".to_string()))?;
+    if options.is_empty() {
+        Ok(Value::Null)
+    } else if let Some(weights) = choice_weights(options, ctx.params.get("weights"))? {
+        Ok(options[weights.sample(ctx.rng)].clone())
+    } else {
+        Ok(options[ctx.rng.gen_range(0..options.len())].clone())
+    }
+}
+
+fn template(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let format = param_str(ctx.params, "format").ok_or_else(|| AppError::Custom("`format` не вказано для `template`".to_string()))?;
+    let mut result = format.to_string();
+    for (key, val) in ctx.row {
+        // Для числових значень теж робимо заміну
+        let val_str = match val {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            _ => "".to_string(),
+        };
+        if !val_str.is_empty() {
+           result = result.replace(&format!("{{{}}}", key), &val_str);
+        }
+    }
+    let rng = &mut *ctx.rng;
+    let result = ctx.generator.template_tokens.replace_all(&result, |caps: &Captures| {
+        if let Some(n) = caps.get(1) {
+            let count: usize = n.as_str().parse().unwrap_or(0);
+            (0..count).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect::<String>()
+        } else if caps.get(2).is_some() {
+            UUIDv4.fake_with_rng::<String, _>(rng)
+        } else {
+            let mut min: i64 = caps[3].parse().unwrap_or(0);
+            let mut max: i64 = caps[4].parse().unwrap_or(0);
+            if min > max {
+                std::mem::swap(&mut min, &mut max);
+            }
+            rng.gen_range(min..=max).to_string()
+        }
+    });
+    if !param_bool(ctx.params, "unique") {
+        return Ok(json!(result));
+    }
+    // Повтор (`john.smith@corp.com`) отримує числовий суфікс: `john.smith2@corp.com`
+    let value = ctx.generator.with_used_values(ctx.column, ctx.params, &mut ctx.state, |used| {
+        let mut value = result.to_string();
+        let mut counter = 1u64;
+        while used.contains(&value) {
+            counter += 1;
+            value = with_counter(&result, counter);
+        }
+        used.insert(value.clone());
+        value
+    })?;
+    Ok(json!(value))
+}
+
+fn fk(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let parent_table = param_str(ctx.params, "references").ok_or_else(|| AppError::Custom("`references` не вказано для `fk`".to_string()))?;
+
+    // `coverage: "even"`: перші рядки по черзі посилаються на кожного батька рівно один раз
+    // (і не отримують NULL), тож за достатньої кількості рядків кожен батько має хоча б одну дитину
+    let covering = param_str(ctx.params, "coverage") == Some("even")
+        && ctx.all_pks.get(parent_table).is_some_and(|pk_pool| (ctx.row_index as usize) < pk_pool.len());
+
+    // Необов'язковий зв'язок: явний NULL замість посилання на батька.
+    // Сідер прибирає `null_chance` для NOT NULL колонок ще до генерації.
+    let null_chance = ctx.params.get("null_chance").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    if !covering && null_chance > 0.0 && ctx.rng.gen_bool(null_chance.min(1.0)) {
+        return Ok(ctx.null());
+    }
+
+    // Якщо пулу взагалі немає, це помилка залежностей.
+    // Це може статися, якщо батьківська таблиця не була в плані.
+    let pk_pool = ctx.all_pks.get(parent_table).ok_or_else(|| AppError::DependencyNotFound(parent_table.to_string()))?;
+    if pk_pool.is_empty() {
+        return Ok(Value::Null);
+    }
+    let distribution = param_str(ctx.params, "distribution").unwrap_or("uniform");
+    let index = match distribution {
+        _ if covering => ctx.row_index as usize,
+        // Зв'язок 1:1 — кожен батько використовується не більше одного разу (вибірка без повернення)
+        _ if param_bool(ctx.params, "unique") => {
+            let (rng, state) = (&mut *ctx.rng, &mut *ctx.state);
+            let remaining = state.unused_parents.entry(ctx.column.to_string()).or_insert_with(|| {
+                let mut indices: Vec<usize> = (0..pk_pool.len()).collect();
+                indices.shuffle(rng);
+                indices
+            });
+            // Рядок генерується повторно (порушив інше обмеження): його батько не вставлений, тож повертаємо
+            // його в кінець черги, щоб не втратити ключ і не спробувати той самий одразу ж
+            if let Some(&(row_index, previous)) = state.chosen_parents.get(ctx.column) {
+                if row_index == ctx.row_index {
+                    remaining.insert(0, previous);
+                }
+            }
+            let index = remaining.pop().ok_or_else(|| AppError::Custom(format!(
+                "Для унікального `fk` '{}' закінчилися батьківські ключі: у таблиці '{}' лише {} рядків. Зменшіть кількість рядків",
+                ctx.column, parent_table, pk_pool.len()
+            )))?;
+            state.chosen_parents.insert(ctx.column.to_string(), (ctx.row_index, index));
+            index
+        }
+        // Степеневий розподіл: перші батьки отримують більшість посилань
+        "zipf" => {
+            let exponent = ctx.params.get("exponent").and_then(|v| v.as_f64()).unwrap_or(1.0);
+            match Zipf::new(pk_pool.len() as u64, exponent) {
+                Ok(zipf) => (zipf.sample(ctx.rng) as usize).clamp(1, pk_pool.len()) - 1,
+                Err(_) => ctx.rng.gen_range(0..pk_pool.len()),
+            }
+        }
+        _ => ctx.rng.gen_range(0..pk_pool.len()),
+    };
+    Ok(pk_pool[index].clone())
+}
+
+fn geo_point(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let [min_lat, min_lon, max_lat, max_lon] = parse_bbox(ctx.params.get("bbox"))
+        .map_err(|e| AppError::Custom(format!("Некоректний `bbox` для `geo_point` '{}': {}", ctx.column, e)))?;
+    let point_name = param_str(ctx.params, "point").unwrap_or("point");
+    let rng = &mut *ctx.rng;
+    let (lat, lon) = *ctx.geo_points.entry(point_name.to_string()).or_insert_with(|| {
+        // 6 знаків після коми — точність близько 10 см
+        let round = |v: f64| (v * 1e6).round() / 1e6;
+        (round(rng.gen_range(min_lat..=max_lat)), round(rng.gen_range(min_lon..=max_lon)))
+    });
+    match param_str(ctx.params, "as").unwrap_or("wkt") {
+        // WKT записує довготу першою
+        "wkt" => Ok(json!(format!("POINT({} {})", lon, lat))),
+        "latlon_pair" => Ok(json!([lat, lon])),
+        "latitude" => Ok(json!(lat)),
+        "longitude" => Ok(json!(lon)),
+        other => Err(AppError::Custom(format!(
+            "Невідомий формат `as` для `geo_point`: '{}' (wkt, latlon_pair, latitude, longitude)",
+            other
+        ))),
+    }
+}
+
+fn words(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let min = param_u64(ctx.params, "min").unwrap_or(2) as usize;
+    let max = param_u64(ctx.params, "max").unwrap_or(5) as usize;
+    let locale = locale(ctx.params, ctx.generator);
+    match param_u64(ctx.params, "max_length") {
+        Some(max_length) => Ok(json!(lorem::words_within(ctx.rng, locale, min..max, max_length as usize))),
+        None => Ok(json!(lorem::words(ctx.rng, locale, min..max).join(" "))),
+    }
+}
+
+fn sequence(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    // Лічильник start, start + step, ...; у режимі append для PK починається після MAX(pk)
+    let mut start = ctx.params.get("start").and_then(|v| v.as_i64()).unwrap_or(1);
+    let step = ctx.params.get("step").and_then(|v| v.as_i64()).unwrap_or(1);
+    if let Some(&floor) = ctx.state.sequence_starts.get(ctx.column) {
+        start = start.max(floor);
+    }
+    Ok(json!(start.saturating_add(step.saturating_mul(ctx.row_index as i64))))
+}
+
+fn number_range(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let mut min = ctx.params.get("min").and_then(|v| v.as_i64()).unwrap_or(0);
+    let mut max = ctx.params.get("max").and_then(|v| v.as_i64()).unwrap_or(100);
+    if min > max {
+        // Якщо Gemini переплутав min та max, міняємо їх місцями
+        std::mem::swap(&mut min, &mut max);
+    }
+    match param_u64(ctx.params, "decimals") {
+        // Дробове число з `decimals` знаками після коми (суми, ціни)
+        Some(decimals) => {
+            let factor = 10f64.powi(decimals.min(10) as i32);
+            Ok(json!((ctx.rng.gen_range(min as f64..=max as f64) * factor).round() / factor))
+        }
+        None => Ok(json!(ctx.rng.gen_range(min..=max))), // ..= включає max, тому min == max є валідним
+    }
+}
+
+fn boolean(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let mut true_chance = ctx.params.get("true_chance").and_then(|v| v.as_f64()).unwrap_or(0.5);
+    // Умовний режим: ймовірність залежить від значення іншого поля цього рядка
+    if let Some(when) = ctx.params.get("when") {
+        let other = when.get("field").and_then(|v| v.as_str()).ok_or_else(|| {
+            AppError::Custom(format!("`when.field` не вказано для `boolean` колонки '{}'", ctx.column))
+        })?;
+        let expected = when.get("equals").unwrap_or(&Value::Null);
+        true_chance = if ctx.row.get(other).is_some_and(|actual| values_equal(actual, expected)) {
+            when.get("true_chance").and_then(|v| v.as_f64()).unwrap_or(true_chance)
+        } else {
+            ctx.params.get("else_true_chance").and_then(|v| v.as_f64()).unwrap_or(true_chance)
+        };
+    }
+    Ok(json!(ctx.rng.gen_bool(true_chance.clamp(0.0, 1.0))))
+}
+
+fn password_hash(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let algorithm = param_str(ctx.params, "algorithm").unwrap_or("bcrypt");
+    let cost = param_u64(ctx.params, "cost").map(|c| c as u32);
+    if param_bool(ctx.params, "random") {
+        // Одноразові хеші для рядків, під якими ніхто не входитиме, тож мінімальна складність
+        let password: String = (&mut *ctx.rng).sample_iter(&Alphanumeric).take(16).map(char::from).collect();
+        return Ok(json!(hash_password(&password, algorithm, cost.unwrap_or(BCRYPT_MIN_COST), true)?));
+    }
+    let password = param_str(ctx.params, "password").unwrap_or(DEFAULT_PASSWORD);
+    let hash = match ctx.state.password_hashes.get(ctx.column) {
+        Some(hash) => hash.clone(),
+        None => {
+            let hash = hash_password(password, algorithm, cost.unwrap_or(bcrypt::DEFAULT_COST), false)?;
+            ctx.state.password_hashes.insert(ctx.column.to_string(), hash.clone());
+            hash
+        }
+    };
+    Ok(json!(hash))
+}
+
+fn currency_code(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let weighted = param_bool(ctx.params, "weighted");
+    Ok(json!(iso_codes::currency_code(ctx.rng, weighted)))
+}
+
+fn country_code(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let weighted = param_bool(ctx.params, "weighted");
+    Ok(json!(iso_codes::country_code(ctx.rng, weighted)))
+}
+
+fn hex_color(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let [r, g, b, a]: [u8; 4] = ctx.rng.gen();
+    if param_bool(ctx.params, "with_alpha") {
+        Ok(json!(format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)))
+    } else {
+        Ok(json!(format!("#{:02x}{:02x}{:02x}", r, g, b)))
+    }
+}
+
+fn json_array(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    // JSON-масив для json/jsonb колонок (на відміну від нативних масивів Postgres):
+    // кожен елемент генерується `element_generator` з параметрами `element_params`
+    let element_generator = param_str(ctx.params, "element_generator").unwrap_or("words");
+    let element_params = ctx.params.get("element_params").and_then(|v| v.as_object())
+        .map(|params| params.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    let min_len = param_u64(ctx.params, "min_len").unwrap_or(1);
+    let max_len = param_u64(ctx.params, "max_len").unwrap_or(5).max(min_len);
+    let element = FieldTemplate {
+        column_name: ctx.column.to_string(),
+        generator: element_generator.to_string(),
+        params: element_params,
+    };
+    let len = ctx.rng.gen_range(min_len..=max_len);
+    let mut elements = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let mut generated = ctx.generator.generate_entity(std::slice::from_ref(&element), ctx.pools, ctx.all_pks, &mut ctx.state, ctx.rng)?;
+        elements.push(generated.remove(ctx.column).unwrap_or(Value::Null));
+    }
+    Ok(Value::Array(elements))
+}
+
+fn bytea(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    // Бінарні дані передаємо через JSON як base64, у БД вони потрапляють уже декодованими
+    let length = param_u64(ctx.params, "length").unwrap_or(16) as usize;
+    let mut bytes = vec![0u8; length];
+    ctx.rng.fill(bytes.as_mut_slice());
+    Ok(json!(BASE64_STANDARD.encode(bytes)))
+}
+
+fn sentence(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let min = param_u64(ctx.params, "min").unwrap_or(5) as usize;
+    let max = param_u64(ctx.params, "max").unwrap_or(10) as usize;
+    let locale = locale(ctx.params, ctx.generator);
+    match param_u64(ctx.params, "max_length") {
+        Some(max_length) => Ok(json!(lorem::sentence_within(ctx.rng, locale, min..max, max_length as usize))),
+        None => Ok(json!(lorem::sentence(ctx.rng, locale, min..max))),
+    }
+}
+
+fn paragraphs(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let min = param_u64(ctx.params, "min").unwrap_or(1) as usize;
+    let max = param_u64(ctx.params, "max").unwrap_or(3) as usize;
+    let locale = locale(ctx.params, ctx.generator);
+    // Range не включає верхню межу, тож `max` абзаців досяжне лише з max + 1
+    let count = min..max.max(min) + 1;
+    match param_u64(ctx.params, "max_length") {
+        Some(max_length) => Ok(json!(lorem::paragraphs_within(ctx.rng, locale, count, max_length as usize))),
+        None => Ok(json!(lorem::paragraphs(ctx.rng, locale, count))),
+    }
+}
+
+fn time_range(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let start = param_str(ctx.params, "start").and_then(parse_time).unwrap_or(NaiveTime::MIN);
+    let end = param_str(ctx.params, "end").and_then(parse_time)
+        .unwrap_or_else(|| NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+    let (start, end) = if start <= end { (start, end) } else { (end, start) };
+    let secs = ctx.rng.gen_range(start.num_seconds_from_midnight()..=end.num_seconds_from_midnight());
+    let time = NaiveTime::from_num_seconds_from_midnight_opt(secs, 0).unwrap_or(start);
+    Ok(json!(time.format("%H:%M:%S").to_string()))
+}
+
+fn interval(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let min_secs = ctx.params.get("min_secs").and_then(|v| v.as_i64()).unwrap_or(0);
+    let max_secs = ctx.params.get("max_secs").and_then(|v| v.as_i64()).unwrap_or(86_400);
+    let (min_secs, max_secs) = if min_secs <= max_secs { (min_secs, max_secs) } else { (max_secs, min_secs) };
+    // Текст у форматі, який Postgres приводить до `interval`
+    Ok(json!(format!("{} seconds", ctx.rng.gen_range(min_secs..=max_secs))))
+}
+
+fn datetime_range(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let start_str = param_str(ctx.params, "start").unwrap_or("2020-01-01");
+    let end_str = param_str(ctx.params, "end").unwrap_or("2024-01-01");
+    let granularity = param_str(ctx.params, "granularity").unwrap_or("datetime");
+    let mode = param_str(ctx.params, "mode").unwrap_or("random");
+
+    if mode == "sequential" {
+        // Рівномірно зростаючі мітки часу: start + row_index * step_secs
+        let step_secs = ctx.params.get("step_secs").and_then(|v| v.as_i64()).unwrap_or(60);
+        let start_dt = parse_datetime(start_str).unwrap_or_else(Utc::now);
+        let offset = chrono::Duration::seconds(step_secs.saturating_mul(ctx.row_index as i64));
+        let dt = start_dt.checked_add_signed(offset).unwrap_or(start_dt);
+        if granularity == "date" {
+            Ok(json!(dt.date_naive().format("%Y-%m-%d").to_string()))
+        } else {
+            Ok(json!(with_precision(ctx, dt).to_rfc3339()))
+        }
+    } else if granularity == "date" {
+        // Для колонок `date` генеруємо дату напряму, без переходу через UTC-мітку часу,
+        // щоб `::date` не зсував її на день залежно від часового поясу
+        let start_date = NaiveDate::parse_from_str(start_str, "%Y-%m-%d").unwrap_or_else(|_| Utc::now().date_naive());
+        let end_date = NaiveDate::parse_from_str(end_str, "%Y-%m-%d").unwrap_or_else(|_| Utc::now().date_naive());
+
+        let date = if start_date >= end_date {
+            start_date
+        } else {
+            let days = (end_date - start_date).num_days();
+            start_date + chrono::Duration::days(ctx.rng.gen_range(0..=days))
+        };
+        Ok(json!(date.format("%Y-%m-%d").to_string()))
+    } else {
+        let start_dt = NaiveDateTime::parse_from_str(&format!("{} 00:00:00", start_str), "%Y-%m-%d %H:%M:%S")
+            .map(|ndt| ndt.and_utc())
+            .unwrap_or_else(|_| Utc::now());
+
+        let end_dt = NaiveDateTime::parse_from_str(&format!("{} 23:59:59", end_str), "%Y-%m-%d %H:%M:%S")
+            .map(|ndt| ndt.and_utc())
+            .unwrap_or_else(|_| Utc::now());
+
+        let start_ts = start_dt.timestamp();
+        let end_ts = end_dt.timestamp();
+
+        let dt = if start_ts >= end_ts {
+            start_dt
+        } else {
+            let random_ts = ctx.rng.gen_range(start_ts..=end_ts);
+            DateTime::from_timestamp(random_ts, 0).unwrap_or_else(Utc::now)
+        };
+        Ok(json!(with_precision(ctx, dt).to_rfc3339()))
+    }
+}
+
+/// `precision: "micros"` замінює долі секунди мітки часу випадковими мікросекундами, щоб мітки часу
+/// не збігалися при сортуванні. У режимі `sequential` порядок зберігається, якщо `step_secs` >= 1.
+fn with_precision(ctx: &mut GeneratorContext, dt: DateTime<Utc>) -> DateTime<Utc> {
+    match param_str(ctx.params, "precision").unwrap_or("seconds") {
+        "micros" => dt.with_nanosecond(ctx.rng.gen_range(0..1_000_000u32) * 1_000).unwrap_or(dt),
+        _ => dt,
+    }
+}
+
+/// Додає лічильник до значення: перед `@` для email-подібних значень, інакше в кінці
+fn with_counter(value: &str, counter: u64) -> String {
+    match value.split_once('@') {
+        Some((local, domain)) => format!("{}{}@{}", local, counter, domain),
+        None => format!("{}{}", value, counter),
+    }
+}
+
+/// Ваги для `choice`: масив поруч з `options` або об'єкт "значення -> вага" (відсутні значення мають вагу 0)
+fn choice_weights(options: &[Value], weights: Option<&Value>) -> AppResult<Option<WeightedIndex<f64>>> {
+    let weights: Vec<f64> = match weights {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Array(list)) if list.len() == options.len() => list.iter().map(|w| w.as_f64().unwrap_or(0.0)).collect(),
+        Some(Value::Array(list)) => return Err(AppError::Custom(format!(
+            "`weights` для `choice` містить {} значень, а `options` — {}", list.len(), options.len()
+        ))),
+        Some(Value::Object(by_label)) => options.iter().map(|option| {
+            let label = option.as_str().map_or_else(|| option.to_string(), str::to_string);
+            by_label.get(&label).and_then(|w| w.as_f64()).unwrap_or(0.0)
+        }).collect(),
+        Some(other) => return Err(AppError::Custom(format!("Невірний формат `weights` для `choice`: {}", other))),
+    };
+    WeightedIndex::new(&weights)
+        .map(Some)
+        .map_err(|e| AppError::Custom(format!("Невірні `weights` для `choice` ({:?}): {}", weights, e)))
+}
+
+/// Порівняння значення поля з очікуваним у `when`: число з плану та рядок з пулу ("5" і 5) вважаються рівними
+fn values_equal(actual: &Value, expected: &Value) -> bool {
+    let as_text = |v: &Value| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+    actual == expected || as_text(actual) == as_text(expected)
+}
+
+/// Межі `geo_point` у форматі `[minLat, minLon, maxLat, maxLon]`; без `bbox` — уся Земля
+fn parse_bbox(bbox: Option<&Value>) -> Result<[f64; 4], String> {
+    let Some(bbox) = bbox else {
+        return Ok([-90.0, -180.0, 90.0, 180.0]);
+    };
+    let numbers: Vec<f64> = bbox
+        .as_array()
+        .ok_or("очікується масив [minLat, minLon, maxLat, maxLon]")?
+        .iter()
+        .map(|v| v.as_f64().ok_or("усі межі мають бути числами"))
+        .collect::<Result<_, _>>()?;
+    let [lat_a, lon_a, lat_b, lon_b]: [f64; 4] = numbers.try_into().map_err(|_| "очікується рівно 4 числа")?;
+    let (min_lat, max_lat) = (lat_a.min(lat_b).max(-90.0), lat_a.max(lat_b).min(90.0));
+    let (min_lon, max_lon) = (lon_a.min(lon_b).max(-180.0), lon_a.max(lon_b).min(180.0));
+    if min_lat > max_lat || min_lon > max_lon {
+        return Err("межі поза діапазоном широти (-90..90) чи довготи (-180..180)".to_string());
+    }
+    Ok([min_lat, min_lon, max_lat, max_lon])
+}
+
+/// Символи для `pk_hash` з `charset: "hex"` (нижній регістр)
+const HEX_CHARS: &[u8] = b"0123456789abcdef";
+/// Символи для `pk_hash` з `charset: "alpha"`
+const ALPHA_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Пароль для `password_hash`, якщо в параметрах не вказано `password`
+const DEFAULT_PASSWORD: &str = "password123";
+/// Мінімальна складність, яку приймає bcrypt
+const BCRYPT_MIN_COST: u32 = 4;
+
+/// Хешує пароль у форматі, який перевіряють бібліотеки автентифікації (`$2b$...` для bcrypt, PHC-рядок для argon2).
+/// `light` зменшує параметри argon2 до мінімальних для одноразових хешів.
+fn hash_password(password: &str, algorithm: &str, cost: u32, light: bool) -> AppResult<String> {
+    match algorithm {
+        "bcrypt" => bcrypt::hash(password, cost).map_err(|e| AppError::Custom(format!("Не вдалося обчислити bcrypt-хеш: {}", e))),
+        "argon2" => {
+            use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+            let argon = if light {
+                let params = argon2::Params::new(argon2::Params::MIN_M_COST, argon2::Params::MIN_T_COST, 1, None)
+                    .map_err(|e| AppError::Custom(format!("Некоректні параметри argon2: {}", e)))?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+            } else {
+                argon2::Argon2::default()
+            };
+            let salt = SaltString::generate(&mut OsRng);
+            argon
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|e| AppError::Custom(format!("Не вдалося обчислити argon2-хеш: {}", e)))
+        }
+        other => Err(AppError::Custom(format!("Невідомий алгоритм `password_hash`: '{}' (підтримуються bcrypt, argon2)", other))),
+    }
+}
+
+/// Розбирає час у форматі `HH:MM` або `HH:MM:SS`
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok()
+}
+
+/// Розбирає `YYYY-MM-DD`, `YYYY-MM-DD HH:MM:SS` або RFC3339 як момент часу в UTC
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(ndt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(ndt.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+/// "Hello, World!" -> "hello-world". Літери інших алфавітів (наприклад, кирилиця) зберігаються
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Обчислює арифметичний вираз над уже згенерованими полями рядка.
+/// Цілий результат повертається як ціле число, інакше — як число з плаваючою комою.
+fn evaluate_formula(formula: &str, entity: &GeneratedEntity) -> Result<Value, EvalexprError> {
+    let mut context = HashMapContext::new();
+    for (name, value) in entity {
+        let variable = match value {
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => EvalValue::Int(i),
+                None => EvalValue::Float(n.as_f64().unwrap_or(0.0)),
+            },
+            Value::Bool(b) => EvalValue::Boolean(*b),
+            // Числа з пулів часто приходять рядками
+            Value::String(s) => match (s.parse::<i64>(), s.parse::<f64>()) {
+                (Ok(i), _) => EvalValue::Int(i),
+                (_, Ok(f)) => EvalValue::Float(f),
+                _ => EvalValue::String(s.clone()),
+            },
+            _ => continue,
+        };
+        context.set_value(name.clone(), variable)?;
+    }
+
+    Ok(match eval_with_context(formula, &context)? {
+        EvalValue::Int(i) => json!(i),
+        EvalValue::Float(f) => json!(f),
+        EvalValue::Boolean(b) => json!(b),
+        EvalValue::String(s) => json!(s),
+        other => json!(other.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::generate_column;
+    use super::*;
+
+    fn generate(name: &str, params: Value, rows: u64) -> Vec<Value> {
+        generate_column(&EntityGenerator::new("en"), name, params, rows).into_iter().map(|v| v.expect("значення згенеровано")).collect()
+    }
+
+    #[test]
+    fn number_range_swaps_min_and_max() {
+        for value in generate("number_range", json!({"min": 10, "max": -5}), 200) {
+            let n = value.as_i64().unwrap();
+            assert!((-5..=10).contains(&n), "{}", n);
+        }
+    }
+
+    #[test]
+    fn number_range_handles_negative_and_single_value_ranges() {
+        let values = generate("number_range", json!({"min": -300, "max": -200}), 200);
+        assert!(values.iter().all(|v| (-300..=-200).contains(&v.as_i64().unwrap())));
+        assert!(values.iter().any(|v| v.as_i64() != values[0].as_i64()), "діапазон має давати різні значення");
+        assert_eq!(generate("number_range", json!({"min": -7, "max": -7}), 3), vec![json!(-7); 3]);
+    }
+
+    #[test]
+    fn number_range_rounds_to_decimals() {
+        for value in generate("number_range", json!({"min": -1, "max": 1, "decimals": 2}), 100) {
+            let f = value.as_f64().unwrap();
+            assert!((-1.0..=1.0).contains(&f), "{}", f);
+            assert_eq!((f * 100.0).round() / 100.0, f);
+        }
+    }
+
+    #[test]
+    fn bytea_decodes_to_requested_length() {
+        for (params, length) in [(json!({}), 16), (json!({"length": 0}), 0), (json!({"length": 1}), 1), (json!({"length": 33}), 33)] {
+            for value in generate("bytea", params.clone(), 5) {
+                let bytes = BASE64_STANDARD.decode(value.as_str().unwrap()).expect("генератор віддає base64");
+                assert_eq!(bytes.len(), length, "{}", params);
+            }
+        }
+    }
+
+    #[test]
+    fn json_array_allows_empty_arrays() {
+        assert_eq!(generate("json_array", json!({"min_len": 0, "max_len": 0}), 3), vec![json!([]); 3]);
+
+        let params = json!({"min_len": 0, "max_len": 2, "element_generator": "number_range", "element_params": {"min": 1, "max": 9}});
+        let lengths: Vec<usize> = generate("json_array", params, 100).iter().map(|v| v.as_array().unwrap().len()).collect();
+        assert!(lengths.iter().all(|&len| len <= 2));
+        assert!(lengths.contains(&0), "min_len 0 має давати порожні масиви");
+        assert!(lengths.contains(&2));
+    }
+
+    /// Мікросекунди мітки часу у форматі RFC 3339 (`...:00.123456+00:00`)
+    fn micros(value: &Value) -> u32 {
+        DateTime::parse_from_rfc3339(value.as_str().unwrap()).unwrap().timestamp_subsec_micros()
+    }
+
+    #[test]
+    fn datetime_range_micros_in_every_mode() {
+        let cases = [
+            json!({"start": "2023-01-01", "end": "2023-12-31", "precision": "micros"}),
+            json!({"start": "2023-01-01", "mode": "sequential", "step_secs": 1, "precision": "micros"}),
+            json!({"start": "2023-06-01", "end": "2023-01-01", "precision": "micros"}),
+        ];
+        for params in cases {
+            let values = generate("datetime_range", params.clone(), 20);
+            assert!(values.iter().any(|v| micros(v) != 0), "{}", params);
+        }
+
+        // Послідовні мітки часу залишаються зростаючими
+        let params = json!({"start": "2023-01-01", "mode": "sequential", "step_secs": 1, "precision": "micros"});
+        let values: Vec<DateTime<chrono::FixedOffset>> = generate("datetime_range", params, 50)
+            .iter()
+            .map(|v| DateTime::parse_from_rfc3339(v.as_str().unwrap()).unwrap())
+            .collect();
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Без `precision` мітки часу цілі секунди
+        for value in generate("datetime_range", json!({"start": "2023-01-01", "mode": "sequential"}), 5) {
+            assert_eq!(micros(&value), 0);
+        }
+    }
+}
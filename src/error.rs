@@ -31,12 +31,37 @@ pub enum AppError {
     #[error("Не вдалося знайти залежність для таблиці '{0}'")]
     DependencyNotFound(String),
 
-    #[error("Знайдено циклічну залежність в схемі БД, заповнення неможливе")]
-    CyclicDependency,
+    #[error("Знайдено циклічну залежність в схемі БД, заповнення неможливе: {0}")]
+    CyclicDependency(String),
+
+    #[error("Циклічна залежність між полями сутності '{0}': {1}")]
+    FieldDependencyCycle(String, String),
+
+    #[error("Рядок для таблиці '{table}' порушує обмеження '{constraint}' після {attempts} спроб: {message}. Згенеровані значення: {values}")]
+    ConstraintViolation {
+        table: String,
+        constraint: String,
+        attempts: u32,
+        message: String,
+        values: String,
+    },
+
+    #[error("{sql} не виконано: {source}")]
+    InsertFailed {
+        /// Запит з підставленими згенерованими значеннями (чутливі колонки приховано)
+        sql: String,
+        source: sqlx::Error,
+    },
+
+    #[error("Вичерпано ліміт запитів до Gemini ({0}, gemini.max_api_calls). Збільште ліміт або увімкніть кеш пулів")]
+    ApiBudgetExhausted(u64),
 
     #[error("Інтерактивну сесію було перервано")]
     Interrupted,
 
+    #[error("Конфігурація не узгоджена зі схемою БД, знайдено проблем: {}: {}", .0.len(), .0.join("; "))]
+    ValidationFailed(Vec<String>),
+
     #[error("Невідомий генератор даних: {0}")]
     UnknownGenerator(String),
 
@@ -1,18 +1,38 @@
 // src/gemini_analyzer.rs
-use crate::db::TableSchema;
+use crate::db::{ForeignKey, TableSchema};
 use crate::error::{AppError, AppResult};
+use crate::report::{ApiTimings, TokenUsage};
+use console::style;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 use serde_json::Value;
 use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
 
 // --- Структури для відповіді від Gemini ---
 #[derive(Deserialize, Debug)]
-struct GeminiResponse { candidates: Vec<Candidate> }
+struct GeminiResponse {
+    candidates: Vec<Candidate>,
+    #[serde(default, rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+}
+#[derive(Deserialize, Debug)]
+struct UsageMetadata {
+    #[serde(default, rename = "promptTokenCount")]
+    prompt_token_count: u64,
+    #[serde(default, rename = "candidatesTokenCount")]
+    candidates_token_count: u64,
+}
 #[derive(Deserialize, Debug)]
-struct Candidate { content: Content }
+struct Candidate {
+    content: Content,
+    #[serde(default, rename = "finishReason")]
+    finish_reason: Option<String>,
+}
 #[derive(Deserialize, Debug)]
 struct Content { parts: Vec<Part> }
 #[derive(Deserialize, Debug)]
@@ -49,29 +69,179 @@ pub struct ArchitecturalPlan {
     pub entity_templates: Vec<EntityTemplate>,
 }
 
+impl ArchitecturalPlan {
+    /// Завантажує план, збережений командою `plan --output`
+    pub fn load(path: &str) -> AppResult<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Custom(format!("Не вдалося прочитати план з '{}': {}", path, e)))?;
+        serde_json::from_str(&content).map_err(|e| AppError::Custom(format!("Некоректний план у '{}': {}", path, e)))
+    }
+
+    pub fn save(&self, path: &str) -> AppResult<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Виводить план у зручному для перегляду вигляді
+    pub fn print(&self) {
+        info!("🗺️  Тема: {}", style(&self.theme).green());
+
+        let mut pool_names: Vec<&String> = self.data_pools.keys().collect();
+        pool_names.sort();
+        info!("💧 Пули даних ({}):", pool_names.len());
+        for name in pool_names {
+            let pool = &self.data_pools[name];
+            info!("   - {}: {}", style(name).cyan(), pool.description);
+            info!("     промпт: {}", pool.gemini_prompt_for_pool);
+        }
+
+        info!("🧩 Шаблони сутностей ({}):", self.entity_templates.len());
+        for template in &self.entity_templates {
+            info!("   {} ({})", style(&template.target_table).bold(), template.entity_name);
+            for field in &template.fields {
+                let params = if field.params.is_empty() {
+                    String::new()
+                } else {
+                    serde_json::to_string(&field.params).unwrap_or_default()
+                };
+                info!("     - {}: {} {}", field.column_name, style(&field.generator).yellow(), params);
+            }
+        }
+    }
+}
+
+/// Публічна адреса Gemini API, до якої додається `/models/{model}:generateContent`
+pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
 pub struct GeminiAnalyzer {
     http_client: Client,
+    /// Префікс адреси API (публічний ендпоінт або внутрішній шлюз), без завершального `/`
+    base_url: String,
     api_key: String,
     model: String,
     temperature: f32,
+    top_p: Option<f32>,
+    max_output_tokens: Option<u32>,
+    /// Ліміт кількості запитів до API за запуск
+    max_api_calls: Option<u64>,
+    /// Сумарне споживання токенів за всі запити цього аналізатора
+    usage: Mutex<TokenUsage>,
+    /// Сумарний час запитів плану та пулів
+    timings: Mutex<ApiTimings>,
 }
 
 impl GeminiAnalyzer {
     pub fn new(api_key: String, model: String, temperature: f32) -> Self {
-        Self { http_client: Client::new(), api_key, model, temperature }
+        Self {
+            http_client: Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key,
+            model,
+            temperature,
+            top_p: None,
+            max_output_tokens: None,
+            max_api_calls: None,
+            usage: Mutex::new(TokenUsage::default()),
+            timings: Mutex::new(ApiTimings::default()),
+        }
+    }
+
+    /// Додаткові параметри `generationConfig`; `None` залишає значення моделі за замовчуванням
+    pub fn with_output_limits(mut self, top_p: Option<f32>, max_output_tokens: Option<u32>) -> Self {
+        self.top_p = top_p;
+        self.max_output_tokens = max_output_tokens;
+        self
+    }
+
+    /// Обмежує кількість запитів до API; після вичерпання ліміту запити завершуються помилкою
+    pub fn with_max_api_calls(mut self, max_api_calls: Option<u64>) -> Self {
+        self.max_api_calls = max_api_calls;
+        self
+    }
+
+    /// Замінює адресу API, наприклад на внутрішній шлюз; `None` залишає публічний ендпоінт
+    pub fn with_base_url(mut self, base_url: Option<&str>) -> Self {
+        if let Some(base_url) = base_url {
+            self.base_url = base_url.trim_end_matches('/').to_string();
+        }
+        self
+    }
+
+    /// Налаштовує HTTP-клієнт для роботи через проксі: `proxy_url` або змінна середовища `HTTPS_PROXY`.
+    /// `accept_invalid_certs` вимикає перевірку TLS-сертифікатів — лише для проксі, що підміняють сертифікати
+    pub fn with_proxy(mut self, proxy_url: Option<&str>, accept_invalid_certs: bool) -> AppResult<Self> {
+        let env_proxy = ["HTTPS_PROXY", "https_proxy"].iter().find_map(|name| std::env::var(name).ok()).filter(|url| !url.is_empty());
+        let proxy_url = proxy_url.map(str::to_string).or(env_proxy);
+        if proxy_url.is_none() && !accept_invalid_certs {
+            return Ok(self);
+        }
+
+        let mut builder = Client::builder();
+        if let Some(url) = &proxy_url {
+            // Адресу проксі не логуємо: вона може містити логін і пароль
+            let proxy = reqwest::Proxy::all(url).map_err(|e| AppError::Custom(format!("Некоректна адреса проксі для Gemini: {}", e)))?;
+            builder = builder.proxy(proxy);
+            info!("🌐 Запити до Gemini надсилаються через проксі");
+        }
+        if accept_invalid_certs {
+            warn!("{}", style("⚠️  Перевірку TLS-сертифікатів для запитів до Gemini вимкнено (gemini.danger_accept_invalid_certs)").yellow());
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        self.http_client = builder.build()?;
+        Ok(self)
+    }
+
+    /// Скільки токенів спожито на цей момент
+    pub fn token_usage(&self) -> TokenUsage {
+        *self.usage.lock().unwrap()
+    }
+
+    /// Скільки часу зайняли запити плану та пулів на цей момент
+    pub fn api_timings(&self) -> ApiTimings {
+        *self.timings.lock().unwrap()
     }
 
     /// Запитує у Gemini архітектурний план
-    pub async fn get_architectural_plan(&self, schemas: &[&TableSchema], lang: &str) -> AppResult<ArchitecturalPlan> {
-        let prompt = self.build_plan_prompt(schemas, lang);
+    pub async fn get_architectural_plan(&self, schemas: &[&TableSchema], foreign_keys: &[&ForeignKey], lang: &str) -> AppResult<ArchitecturalPlan> {
+        let started = Instant::now();
+        let plan = self.fetch_architectural_plan(schemas, foreign_keys, lang).await;
+        self.timings.lock().unwrap().plan += started.elapsed();
+        plan
+    }
+
+    async fn fetch_architectural_plan(&self, schemas: &[&TableSchema], foreign_keys: &[&ForeignKey], lang: &str) -> AppResult<ArchitecturalPlan> {
+        let prompt = self.build_plan_prompt(schemas, foreign_keys, lang);
         let json_text = self.query_gemini(&prompt).await?;
-        let plan: ArchitecturalPlan = serde_json::from_str(&json_text)
-            .map_err(|e| AppError::Custom(format!("Помилка парсингу плану від Gemini: {}. Відповідь: {}", e, json_text)))?;
+        let plan: ArchitecturalPlan = serde_json::from_str(&json_text).map_err(|e| {
+            // Обірваний посередині JSON майже завжди означає, що відповідь вперлася в ліміт токенів
+            let hint = if e.is_eof() {
+                " Схоже, відповідь обрізано: спробуйте збільшити `gemini.max_output_tokens`."
+            } else {
+                ""
+            };
+            AppError::Custom(format!("Помилка парсингу плану від Gemini: {}.{} Відповідь: {}", e, hint, json_text))
+        })?;
         Ok(plan)
     }
 
+    /// Відсутній ключ не виправиться повторною спробою, тож перевіряємо його до циклу повторів
+    fn ensure_api_key(&self) -> AppResult<()> {
+        if self.api_key.is_empty() {
+            return Err(AppError::Custom("API ключ для Gemini не встановлено: задайте змінну середовища GEMINI_API_KEY (або названу в `gemini.api_key_env`) чи `gemini.api_key` у конфігурації".to_string()));
+        }
+        Ok(())
+    }
+
     /// Запитує у Gemini дані для заповнення конкретного пулу
     pub async fn get_pool_data(&self, prompt: &str) -> AppResult<Vec<String>> {
+        let started = Instant::now();
+        let data = self.fetch_pool_data(prompt).await;
+        self.timings.lock().unwrap().pools += started.elapsed();
+        data
+    }
+
+    async fn fetch_pool_data(&self, prompt: &str) -> AppResult<Vec<String>> {
+        self.ensure_api_key()?;
         // ВИПРАВЛЕНО: Додаємо системну обгортку до промпту
         let final_prompt = format!(
             "Ти - генератор даних. Твоя єдина задача - виконати наступну інструкцію і повернути ЛИШЕ валідний JSON без жодного додаткового тексту, коментарів чи пояснень.\n\nІнструкція: {}",
@@ -87,13 +257,15 @@ impl GeminiAnalyzer {
                         Ok(data) => return Ok(data), // Успіх, виходимо
                         Err(e) => {
                             // Помилка парсингу, логуємо і спробуємо ще раз
-                            eprintln!("⚠️ Спроба {}: Помилка парсингу відповіді для пулу. Помилка: {}. Спробую ще раз...", attempt + 1, e);
+                            warn!("⚠️ Спроба {}: Помилка парсингу відповіді для пулу. Помилка: {}. Спробую ще раз...", attempt + 1, e);
                         }
                     }
                 }
+                // Повтор лише витратив би ще один запит понад ліміт
+                Err(e @ AppError::ApiBudgetExhausted(_)) => return Err(e),
                 Err(e) => {
                      // Помилка мережі або API, логуємо і спробуємо ще раз
-                    eprintln!("⚠️ Спроба {}: Помилка запиту до Gemini. Помилка: {}. Спробую ще раз...", attempt + 1, e);
+                    warn!("⚠️ Спроба {}: Помилка запиту до Gemini. Помилка: {}. Спробую ще раз...", attempt + 1, e);
                 }
             }
             // Чекаємо перед наступною спробою
@@ -145,39 +317,85 @@ impl GeminiAnalyzer {
     }
 
     async fn query_gemini(&self, prompt: &str) -> AppResult<String> {
-        if self.api_key.is_empty() { return Err(AppError::Custom("API ключ для Gemini не встановлено".to_string())); }
-        
-        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}", self.model, self.api_key);
+        self.ensure_api_key()?;
+        {
+            let mut usage = self.usage.lock().unwrap();
+            if self.max_api_calls.is_some_and(|max| usage.requests >= max) {
+                return Err(AppError::ApiBudgetExhausted(usage.requests));
+            }
+            usage.requests += 1;
+        }
+
+        let url = format!("{}/models/{}:generateContent?key={}", self.base_url, self.model, self.api_key);
+        let mut generation_config = json!({
+            "temperature": self.temperature,
+            "response_mime_type": "application/json"
+        });
+        if let Some(top_p) = self.top_p {
+            generation_config["topP"] = json!(top_p);
+        }
+        if let Some(max_output_tokens) = self.max_output_tokens {
+            generation_config["maxOutputTokens"] = json!(max_output_tokens);
+        }
         let body = json!({
             "contents": [{ "parts": [{ "text": prompt }] }],
-            "generationConfig": {
-                "temperature": self.temperature,
-                "response_mime_type": "application/json"
-            }
+            "generationConfig": generation_config
         });
 
+        debug!("Запит до Gemini (модель: {}, довжина промпту: {} символів)", self.model, prompt.len());
         let response = self.http_client.post(&url).json(&body).send().await?;
         if !response.status().is_success() {
             return Err(AppError::Custom(format!("Помилка від Gemini API: {}", response.text().await?)));
         }
 
         let gemini_response = response.json::<GeminiResponse>().await?;
-        gemini_response
-            .candidates.into_iter().next()
+        if let Some(meta) = &gemini_response.usage_metadata {
+            let mut usage = self.usage.lock().unwrap();
+            usage.prompt_tokens += meta.prompt_token_count;
+            usage.output_tokens += meta.candidates_token_count;
+        }
+        let candidate = gemini_response.candidates.into_iter().next();
+        if candidate.as_ref().and_then(|c| c.finish_reason.as_deref()) == Some("MAX_TOKENS") {
+            warn!("⚠️ Відповідь Gemini обрізано через ліміт токенів. Збільште `gemini.max_output_tokens`.");
+        }
+        candidate
             .and_then(|c| c.content.parts.into_iter().next())
             .map(|p| p.text.trim().to_string())
             .ok_or_else(|| AppError::Custom("Gemini API не повернув JSON-текст".to_string()))
     }
 
-    fn build_plan_prompt(&self, schemas: &[&TableSchema], lang: &str) -> String {
+    fn build_plan_prompt(&self, schemas: &[&TableSchema], foreign_keys: &[&ForeignKey], lang: &str) -> String {
         let mut schemas_str = String::new();
         for schema in schemas {
             schemas_str.push_str(&format!("\n--- Table: {} ---\n", schema.name));
             for col in &schema.columns {
-                schemas_str.push_str(&format!("- {} (type: {}, nullable: {}, default: {})\n", col.name, col.data_type, col.is_nullable, col.column_default.as_deref().unwrap_or("none")));
+                if col.is_enum() {
+                    schemas_str.push_str(&format!("- {} (type: enum {}, allowed values: [{}], nullable: {}, default: {})\n", col.name, col.udt_name, col.enum_labels.join(", "), col.is_nullable, col.column_default.as_deref().unwrap_or("none")));
+                } else {
+                    // Назва домену (`email_address`) підказує зміст колонки краще за базовий тип
+                    let data_type = match &col.domain {
+                        Some((_, domain)) => format!("{} (domain {})", col.data_type, domain),
+                        None => col.data_type.clone(),
+                    };
+                    schemas_str.push_str(&format!("- {} (type: {}, nullable: {}, default: {})\n", col.name, data_type, col.is_nullable, col.column_default.as_deref().unwrap_or("none")));
+                }
+            }
+            if !schema.check_constraints.is_empty() {
+                schemas_str.push_str("Check constraints (generated values MUST satisfy them):\n");
+                for check in &schema.check_constraints {
+                    schemas_str.push_str(&format!("- {}: {}\n", check.name, check.definition));
+                }
             }
         }
 
+        let mut foreign_keys_str = String::new();
+        for fk in foreign_keys {
+            foreign_keys_str.push_str(&format!("- {}.{} references {}.{}\n", fk.from_table, fk.from_column, fk.to_table, fk.to_column));
+        }
+        if foreign_keys_str.is_empty() {
+            foreign_keys_str.push_str("(none)\n");
+        }
+
         let lang_instruction = if lang == "uk" {
             "Provide all descriptions and data generation prompts in Ukrainian."
         } else {
@@ -194,15 +412,33 @@ Follow the JSON structure from the example below EXACTLY.
 Most importantly, for the "generator" field, you MUST use ONLY one of the values from the "Allowed Generators" list. DO NOT invent new generator names.
 
 ### Allowed Generators List ###
-- `pk_hash`: For string-based primary keys. (params: {{"length": number}})
+- `pk_hash`: For string-based primary keys. "prefix" is prepended and not counted in "length", e.g. Stripe-style "usr_" IDs. (params: {{"length": number, "prefix": string, "charset": "alnum" | "hex" | "alpha"}})
 - `from_pool`: To get a random value from a data pool. (params: {{"pool_name": "string"}})
-- `template`: To combine fields into a new string. (params: {{"format": "string with {{field_name}} placeholders"}})
-- `fk`: For foreign keys. (params: {{"references": "table_name"}})
-- `words`: For short text (2-5 words). (params: {{"min": number, "max": number}})
-- `sentence`: For longer text (1-3 sentences). (params: {{"min": number, "max": number}})
-- `number_range`: For all numeric types (integer, decimal). (params: {{"min": number, "max": number}})
-- `boolean`: For boolean values. (params: {{"true_chance": float_between_0_and_1}})
-- `datetime_range`: For all date and time types (timestamp, date). (params: {{"start": "YYYY-MM-DD", "end": "YYYY-MM-DD"}})
+- `template`: To combine fields into a new string. Besides {{field_name}}, supports {{random_digits:N}}, {{uuid}} and {{random_int:min-max}} tokens. For UNIQUE columns (emails, logins) set "unique": true so repeats get a numeric suffix; "scope": "global:<name>" keeps values unique across all tables sharing that name. (params: {{"format": "string with {{field_name}} placeholders", "unique": boolean, "scope": "global:<name>"}})
+- `fk`: For foreign keys. Optionally skew references with a power-law distribution. For nullable FK columns "null_chance" leaves some rows without a parent. Use "unique": true for one-to-one relations (FK column with a unique constraint). "coverage": "even" makes the first rows reference every parent once so no parent is left without children. (params: {{"references": "table_name", "distribution": "uniform" | "zipf", "exponent": number, "null_chance": float_between_0_and_1, "unique": boolean, "coverage": "even"}})
+- `constant`: For a fixed value repeated in every row, e.g. a tenant id or a discriminator column. (params: {{"value": any_json_value}})
+- `expression`: For numeric columns computed from other numeric fields of the same row, e.g. "quantity * unit_price". (params: {{"formula": "arithmetic expression over field names"}})
+- `slug`: For URL slugs derived from another text field of the same row, e.g. "Hello World" -> "hello-world". Use "unique": true for columns with a unique constraint, with the same optional "scope" as `template`. (params: {{"from": "field_name", "unique": boolean, "scope": "global:<name>"}})
+- `company`: For company / organization names. No data pool needed. (params: {{}})
+- `job_title`: For job titles and positions. No data pool needed. (params: {{}})
+- `url`: For links such as website or avatar_url columns. (params: {{"scheme": "https" | "http", "with_path": boolean}})
+- `choice`: To pick one of the fixed values, e.g. for enum columns. Optional "weights" (same length as "options") skews the distribution. (params: {{"options": ["value1", "value2"], "weights": [0.8, 0.2]}})
+- `words`: For short text (2-5 words). (params: {{"min": number, "max": number, "locale": "en" | "uk"}})
+- `sentence`: For longer text (1-3 sentences). (params: {{"min": number, "max": number, "locale": "en" | "uk"}})
+- `sequence`: For integer primary keys without a DB default (no serial/identity): 1, 2, 3, ... (params: {{"start": number, "step": number}})
+- `paragraphs`: For long text such as article bodies and descriptions: several paragraphs separated by blank lines. (params: {{"min": number, "max": number, "locale": "en" | "uk"}})
+- `number_range`: For all numeric types (integer, decimal, money). Use "decimals" for fractional values such as prices; `money` columns always get 2 decimals. (params: {{"min": number, "max": number, "decimals": number}})
+- `boolean`: For boolean values. To correlate with another column of the same row, add "when" and "else_true_chance", e.g. is_published is likely true when status is 'approved'. (params: {{"true_chance": float_between_0_and_1, "when": {{"field": "status", "equals": "approved", "true_chance": 0.9}}, "else_true_chance": 0.1}})
+- `password_hash`: For password / password_hash columns of auth tables. All rows get the hash of the same known password so test logins work. (params: {{"password": "password123", "algorithm": "bcrypt" | "argon2"}})
+- `geo_point`: For coordinates within a bounding box. Use "as": "wkt" for PostGIS geography/geometry columns; for separate latitude/longitude columns use "as": "latitude" / "longitude" with the same "point" name so both come from one point. (params: {{"bbox": [minLat, minLon, maxLat, maxLon], "as": "wkt" | "latlon_pair" | "latitude" | "longitude", "point": "string"}})
+- `currency_code`: For ISO 4217 currency columns, e.g. "USD". "weighted": true favours common currencies. (params: {{"weighted": boolean}})
+- `country_code`: For ISO 3166-1 alpha-2 country columns, e.g. "UA". "weighted": true favours common countries. (params: {{"weighted": boolean}})
+- `hex_color`: For color columns in `#RRGGBB` format, or `#RRGGBBAA` with "with_alpha": true. (params: {{"with_alpha": boolean}})
+- `json_array`: For json/jsonb columns holding a list, e.g. tags `["rust","db"]`. Each element comes from "element_generator" with "element_params". (params: {{"element_generator": "words", "element_params": {{"min": 1, "max": 2}}, "min_len": number, "max_len": number}})
+- `bytea`: For binary (`bytea`) columns; produces random bytes. (params: {{"length": number_of_bytes}})
+- `time_range`: For `time` columns without a date, e.g. shift start times. (params: {{"start": "HH:MM", "end": "HH:MM"}})
+- `interval`: For `interval` (duration) columns, e.g. session length or SLA. (params: {{"min_secs": number, "max_secs": number}})
+- `datetime_range`: For all date and time types (timestamp, date). Use "granularity": "date" for `date` columns. For time-series/event tables use "mode": "sequential" so values increase by "step_secs" per row. "precision": "micros" adds random sub-second parts to avoid ties. (params: {{"start": "YYYY-MM-DD", "end": "YYYY-MM-DD", "granularity": "date" | "datetime", "mode": "random" | "sequential", "step_secs": number, "precision": "seconds" | "micros"}})
 
 ### EXAMPLE OF THE REQUIRED JSON STRUCTURE ###
 {{
@@ -232,9 +468,12 @@ Now, analyze the following schemas and generate the plan. Remember to use ONLY t
 
 ### SCHEMAS TO ANALYZE ###
 {schemas_str}
-"#,
+### FOREIGN KEYS ###
+Every column listed here MUST use the `fk` generator with "references" set to the referenced table.
+{foreign_keys_str}"#,
             lang_instruction = lang_instruction,
-            schemas_str = schemas_str
+            schemas_str = schemas_str,
+            foreign_keys_str = foreign_keys_str
         )
     }
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+// src/init.rs
+// Генерація стартового config.toml за схемою БД (команда `init`)
+use crate::seeder::Seeder;
+use petgraph::algo::toposort;
+use petgraph::graphmap::DiGraphMap;
+use std::fmt::Write;
+use tracing::warn;
+
+/// Таблиці схеми в порядку заповнення: батьки перед дітьми.
+/// Вузли додаються за алфавітом, тож порядок детермінований і повторний `init` дає той самий файл.
+fn tables_in_seeding_order(seeder: &Seeder) -> Vec<String> {
+    let full_graph = seeder.build_full_dependency_graph();
+    let mut names: Vec<&str> = full_graph.nodes().collect();
+    names.sort_unstable();
+
+    let mut graph = DiGraphMap::<&str, ()>::new();
+    for &name in &names {
+        graph.add_node(name);
+    }
+    for (parent, child, _) in full_graph.all_edges() {
+        graph.add_edge(parent, child, ());
+    }
+
+    match toposort(&graph, None) {
+        Ok(sorted) => sorted.into_iter().map(str::to_string).collect(),
+        Err(_) => {
+            warn!("⚠️  Схема містить циклічні залежності між таблицями, таблиці впорядковано за алфавітом");
+            names.into_iter().map(str::to_string).collect()
+        }
+    }
+}
+
+/// Екранує рядок для TOML (basic string)
+fn toml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Формує текст стартової конфігурації: `[database]`, закоментовані `[gemini]`/`[generation]`
+/// та `seeding_plan` з усіма таблицями схеми по `default_rows` рядків
+pub fn starter_config(seeder: &Seeder, database_url: &str, schema: Option<&str>, default_rows: u32) -> String {
+    let mut out = String::new();
+    // Запис у String не може завершитися помилкою
+    let _ = writeln!(out, "# Стартова конфігурація, згенерована `db_seeder init`.");
+    let _ = writeln!(out, "# Приберіть зайві таблиці та налаштуйте кількість рядків. Усі параметри описані в config.example.toml");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[database]");
+    let _ = writeln!(out, "url = {}", toml_string(database_url));
+    match schema {
+        Some(schema) => {
+            let _ = writeln!(out, "schema = {}", toml_string(schema));
+        }
+        None => {
+            let _ = writeln!(out, "# schema = \"public\"");
+        }
+    }
+    out.push_str(
+        r#"
+# --- Конфігурація Gemini API ---
+# [gemini]
+# model = "gemini-1.5-flash-latest"
+# temperature = 0.7
+# pool_cache_dir = ".db-seeder-cache"
+
+# --- Налаштування генерації ---
+# [generation]
+# language = "uk"
+# row_retries = 3
+# transaction_mode = "per_table"
+"#,
+    );
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "default_rows = {}", default_rows);
+    for table in tables_in_seeding_order(seeder) {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "[[seeding_plan]]");
+        let _ = writeln!(out, "table = {}", toml_string(&table));
+        let _ = writeln!(out, "rows = {}", default_rows);
+    }
+    out
+}
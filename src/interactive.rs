@@ -1,7 +1,7 @@
 // src/interactive.rs
-use crate::config::SeedingTask;
+use crate::config::{Rows, SeedingTask};
 use crate::db::DbSchema;
-use crate::error::{AppError, AppResult};
+use crate::error::AppResult;
 use console::{style, Term};
 use dialoguer::theme::Theme;
 use dialoguer::Input;
@@ -34,9 +34,9 @@ impl Theme for CustomTheme {
         default: Option<&str>,
     ) -> std::fmt::Result {
         if let Some(default) = default {
-            write!(f, "{_green}?{_reset} {} {_dim}({default}){_reset} ", prompt, _green = "\x1b[32m", _reset = "\x1b[0m", _dim = "\x1b[2m")
+            write!(f, "\x1b[32m?\x1b[0m {} \x1b[2m({default})\x1b[0m ", prompt)
         } else {
-            write!(f, "{_green}?{_reset} {} ", prompt, _green = "\x1b[32m", _reset = "\x1b[0m")
+            write!(f, "\x1b[32m?\x1b[0m {} ", prompt)
         }
     }
 }
@@ -55,10 +55,10 @@ pub fn run_interactive_mode(
     loop {
         term.clear_screen()?;
         println!("{}", style("Інтерактивний вибір таблиць для заповнення:").bold());
-        println!("(використовуйте ↑/↓, 'пробіл' для вибору, 'enter' для продовження, 'q' для виходу)\n");
+        println!("(використовуйте ↑/↓, 'enter' або 'пробіл' для вибору, 'q' чи Esc для виходу без заповнення)\n");
 
         let mut items = Vec::new();
-        for (_i, &table_name) in table_names.iter().enumerate() {
+        for &table_name in table_names.iter() {
             let prefix = if selections.contains(table_name) { "[*]" } else { "[ ]" };
             
             // Шукаємо залежності (батьків)
@@ -72,11 +72,15 @@ pub fn run_interactive_mode(
             items.push(format!("{} {}{}", prefix, style(table_name).green(), style(parent_str).yellow()));
         }
 
-        let selection = dialoguer::Select::with_theme(&theme)
+        // `Select` повертає `None` на 'q' та Esc — це звичайний вихід, а не переривання
+        let Some(selection) = dialoguer::Select::with_theme(&theme)
             .items(&items)
             .default(0)
             .interact_on_opt(&term)?
-            .ok_or(AppError::Interrupted)?;
+        else {
+            println!("Вихід без заповнення.");
+            return Ok(Vec::new());
+        };
 
         let selected_table = table_names[selection];
 
@@ -144,7 +148,7 @@ pub fn run_interactive_mode(
 
         plan.push(SeedingTask {
             table: table_name.to_string(),
-            rows,
+            rows: Rows::Fixed(rows),
             ..Default::default()
         });
     }
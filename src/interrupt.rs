@@ -0,0 +1,29 @@
+// src/interrupt.rs
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+/// Код виходу після переривання через Ctrl-C (128 + SIGINT)
+pub const EXIT_CODE: i32 = 130;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Встановлює обробник Ctrl-C. Перше натискання просить зупинитися на найближчій безпечній точці
+/// (відкат поточної транзакції), друге — завершує процес негайно.
+pub fn install_handler() {
+    tokio::spawn(async {
+        loop {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            if REQUESTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(EXIT_CODE);
+            }
+            warn!("🛑 Отримано Ctrl-C, зупиняюся (натисніть ще раз для негайного виходу)...");
+        }
+    });
+}
+
+/// Чи просив користувач перервати роботу
+pub fn is_requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,42 @@
+// src/lib.rs
+//! Бібліотека для інтелектуального заповнення БД. Бінарний файл `db_seeder` — тонка обгортка над нею,
+//! тож сідер можна вбудувати, наприклад, в інтеграційні тести.
+pub mod config;
+pub mod db;
+mod entity_generator;
+pub mod error;
+pub mod gemini_analyzer;
+pub mod init;
+pub mod interactive;
+pub mod interrupt;
+mod iso_codes;
+mod lorem;
+mod pool_cache;
+pub mod report;
+pub mod seeder;
+pub mod validator;
+
+pub use crate::config::AppConfig;
+pub use crate::db::DbClient;
+pub use crate::entity_generator::{GeneratorContext, GeneratorFn};
+pub use crate::error::{AppError, AppResult};
+pub use crate::gemini_analyzer::ArchitecturalPlan;
+pub use crate::report::RunSummary;
+pub use crate::seeder::{RunOptions, Seeder};
+
+use sqlx::PgPool;
+
+/// Заповнює БД через наявний пул з'єднань за готовим архітектурним планом, не запитуючи план у Gemini.
+/// Gemini потрібен лише для пулів даних, якщо план їх містить і вони не закешовані.
+pub async fn seed_with_plan(pool: PgPool, config: &AppConfig, plan: ArchitecturalPlan) -> AppResult<RunSummary> {
+    let mut db_client = DbClient::from_pool(pool);
+    if let Some(schema) = &config.database.schema {
+        db_client = db_client.with_schema(schema);
+    }
+    let seeder = Seeder::new(db_client).await?;
+    let options = RunOptions {
+        architectural_plan: Some(plan),
+        ..RunOptions::default()
+    };
+    seeder.run(config, &options).await
+}
@@ -0,0 +1,191 @@
+// src/lorem.rs
+// Локалізовані текстові генератори: lorem, назви компаній, посади
+use fake::locales::{AR_SA, EN, FR_FR, JA_JP, PT_BR, ZH_CN};
+use fake::{faker, Fake};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::ops::Range;
+
+/// У `fake` немає української локалі, а lorem в усіх його локалях однаковий (латина),
+/// тому для "uk" використовуємо власний словник.
+const UK_WORDS: &[&str] = &[
+    "місто", "річка", "поле", "ліс", "день", "ніч", "світло", "дорога", "дім", "вікно",
+    "сонце", "вітер", "море", "гора", "небо", "земля", "хліб", "вода", "книга", "слово",
+    "пісня", "мова", "час", "рік", "весна", "літо", "осінь", "зима", "ранок", "вечір",
+    "робота", "справа", "думка", "ідея", "план", "мета", "шлях", "крок", "погляд", "голос",
+    "новий", "старий", "великий", "малий", "теплий", "світлий", "тихий", "швидкий", "добрий", "ясний",
+    "зелений", "синій", "жовтий", "білий", "далекий", "близький", "важливий", "простий", "цікавий", "вільний",
+    "працює", "будує", "пише", "читає", "бачить", "знає", "шукає", "відкриває", "створює", "говорить",
+    "та", "і", "але", "або", "для", "про", "після", "перед", "над", "біля",
+    "сьогодні", "завжди", "часто", "тепер", "разом", "швидко", "повільно", "тут", "там", "знову",
+    "команда", "проєкт", "система", "сервіс", "клієнт", "замовлення", "товар", "ціна", "звіт", "подія",
+];
+
+/// Повертає випадкові слова вказаною мовою; невідомі мови отримують англійський lorem
+pub fn words<R: Rng + ?Sized>(rng: &mut R, locale: &str, count: Range<usize>) -> Vec<String> {
+    match locale {
+        "uk" => {
+            let n = if count.is_empty() { count.start } else { rng.gen_range(count) };
+            (0..n).map(|_| UK_WORDS.choose(rng).unwrap().to_string()).collect()
+        }
+        _ => faker::lorem::en::Words(count).fake_with_rng(rng),
+    }
+}
+
+/// Речення з кількістю слів у межах `count`, з великої літери та крапкою в кінці
+pub fn sentence<R: Rng + ?Sized>(rng: &mut R, locale: &str, count: Range<usize>) -> String {
+    match locale {
+        "uk" => {
+            let text = words(rng, locale, count).join(" ");
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+                None => String::new(),
+            }
+        }
+        _ => faker::lorem::en::Sentence(count).fake_with_rng(rng),
+    }
+}
+
+/// Викликає `fake::faker::*::raw` генератор з локаллю, що відповідає коду мови.
+/// Для мов, яких немає в `fake` (зокрема "uk"), використовується англійська.
+macro_rules! localized {
+    ($faker:path, $locale:expr, $rng:expr) => {
+        match $locale {
+            "fr" => $faker(FR_FR).fake_with_rng::<String, _>($rng),
+            "pt" => $faker(PT_BR).fake_with_rng::<String, _>($rng),
+            "zh" => $faker(ZH_CN).fake_with_rng::<String, _>($rng),
+            "ja" => $faker(JA_JP).fake_with_rng::<String, _>($rng),
+            "ar" => $faker(AR_SA).fake_with_rng::<String, _>($rng),
+            _ => $faker(EN).fake_with_rng::<String, _>($rng),
+        }
+    };
+}
+
+/// Слова, що разом (через пробіл) вміщуються в `max_length` символів: зайві слова з кінця відкидаються.
+/// Слово, довше за ліміт, обрізається — інакше значення не вставити в колонку.
+pub fn words_within<R: Rng + ?Sized>(rng: &mut R, locale: &str, count: Range<usize>, max_length: usize) -> String {
+    let mut words = words(rng, locale, count);
+    while words.len() > 1 && words.join(" ").chars().count() > max_length {
+        words.pop();
+    }
+    truncate_chars(&words.join(" "), max_length)
+}
+
+/// Речення, що вміщується в `max_length` символів: поки не вміщується, генеруємо нове з меншою кількістю слів
+pub fn sentence_within<R: Rng + ?Sized>(rng: &mut R, locale: &str, mut count: Range<usize>, max_length: usize) -> String {
+    loop {
+        let text = sentence(rng, locale, count.clone());
+        let word_count = text.split_whitespace().count();
+        if text.chars().count() <= max_length || word_count <= 1 {
+            return truncate_chars(&text, max_length);
+        }
+        // Кожна спроба має щонайменше на одне слово менше, тож цикл завершується
+        count = count.start.min(word_count - 1).max(1)..word_count;
+    }
+}
+
+/// Абзаци (кількість у межах `count`), розділені порожнім рядком
+pub fn paragraphs<R: Rng + ?Sized>(rng: &mut R, locale: &str, count: Range<usize>) -> String {
+    let paragraphs: Vec<String> = match locale {
+        "uk" => {
+            let n = if count.is_empty() { count.start } else { rng.gen_range(count) };
+            (0..n)
+                .map(|_| {
+                    let sentences = rng.gen_range(3..7);
+                    (0..sentences).map(|_| sentence(rng, locale, 4..12)).collect::<Vec<_>>().join(" ")
+                })
+                .collect()
+        }
+        // `fake` розділяє речення абзацу переводом рядка, а нам потрібен суцільний абзац
+        _ => faker::lorem::en::Paragraphs(count)
+            .fake_with_rng::<Vec<String>, _>(rng)
+            .into_iter()
+            .map(|paragraph| paragraph.replace('\n', " "))
+            .collect(),
+    };
+    paragraphs.join("\n\n")
+}
+
+/// Абзаци, що вміщуються в `max_length` символів: зайві абзаци з кінця відкидаються,
+/// а єдиний задовгий абзац обрізається по межі слова
+pub fn paragraphs_within<R: Rng + ?Sized>(rng: &mut R, locale: &str, count: Range<usize>, max_length: usize) -> String {
+    let text = paragraphs(rng, locale, count);
+    let mut kept: Vec<&str> = text.split("\n\n").collect();
+    while kept.len() > 1 && kept.join("\n\n").chars().count() > max_length {
+        kept.pop();
+    }
+    let text = kept.join("\n\n");
+    if text.chars().count() <= max_length {
+        return text;
+    }
+    let truncated = truncate_chars(&text, max_length);
+    match truncated.rfind(char::is_whitespace) {
+        Some(end) if end > 0 => truncated[..end].to_string(),
+        _ => truncated,
+    }
+}
+
+fn truncate_chars(text: &str, max_length: usize) -> String {
+    text.chars().take(max_length).collect()
+}
+
+/// Назва компанії, наприклад "Schmidt and Sons"
+pub fn company<R: Rng + ?Sized>(rng: &mut R, locale: &str) -> String {
+    localized!(faker::company::raw::CompanyName, locale, rng)
+}
+
+/// Посада, наприклад "Senior Data Engineer"
+pub fn job_title<R: Rng + ?Sized>(rng: &mut R, locale: &str) -> String {
+    localized!(faker::job::raw::Title, locale, rng)
+}
+
+/// Пули, які можна заповнити локально через `fake` замість запиту до Gemini (`gemini.prefer_local_pools`).
+/// Пул розпізнається за закінченням назви, тож `customer_emails` теж вважається `emails`.
+/// Специфічніші назви йдуть першими, щоб `first_names` не сприйняти як `names`.
+const LOCAL_POOL_KINDS: &[&str] = &[
+    "first_names", "last_names", "full_names", "names", "emails", "usernames", "phone_numbers", "phones",
+    "cities", "countries", "street_names", "streets", "company_names", "companies", "job_titles",
+];
+
+/// Чи має мова власну локаль у `fake`. Для інших ("uk") імена, міста тощо краще генерувати через Gemini.
+fn has_faker_locale(locale: &str) -> bool {
+    matches!(locale, "en" | "fr" | "pt" | "zh" | "ja" | "ar")
+}
+
+/// Заповнює пул локально, якщо його назва відповідає відомому виду даних. `None` — пул потрібно запитати в Gemini.
+/// Значення в пулі унікальні, але їх може бути менше за `count`, якщо `fake` не має достатньо варіантів.
+pub fn local_pool<R: Rng + ?Sized>(rng: &mut R, pool_name: &str, locale: &str, count: usize) -> Option<Vec<String>> {
+    let name = pool_name.to_lowercase();
+    let kind = *LOCAL_POOL_KINDS.iter().find(|kind| name.ends_with(*kind))?;
+    // Email та логіни не залежать від мови, решта — лише для мов, що є у `fake`
+    let locale_neutral = matches!(kind, "emails" | "usernames");
+    if !locale_neutral && !has_faker_locale(locale) {
+        return None;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count * 3 {
+        if values.len() >= count {
+            break;
+        }
+        let value = match kind {
+            "first_names" => localized!(faker::name::raw::FirstName, locale, rng),
+            "last_names" => localized!(faker::name::raw::LastName, locale, rng),
+            "full_names" | "names" => localized!(faker::name::raw::Name, locale, rng),
+            "emails" => faker::internet::en::SafeEmail().fake_with_rng(rng),
+            "usernames" => faker::internet::en::Username().fake_with_rng(rng),
+            "phone_numbers" | "phones" => localized!(faker::phone_number::raw::PhoneNumber, locale, rng),
+            "cities" => localized!(faker::address::raw::CityName, locale, rng),
+            "countries" => localized!(faker::address::raw::CountryName, locale, rng),
+            "street_names" | "streets" => localized!(faker::address::raw::StreetName, locale, rng),
+            "company_names" | "companies" => company(rng, locale),
+            _ => job_title(rng, locale),
+        };
+        if seen.insert(value.clone()) {
+            values.push(value);
+        }
+    }
+    Some(values)
+}
@@ -1,18 +1,15 @@
 // src/main.rs
-mod config;
-mod db;
-mod entity_generator;
-mod error;
-mod gemini_analyzer;
-mod interactive;
-mod seeder;
-
-use crate::config::AppConfig;
-use crate::db::DbClient;
-use crate::error::AppResult;
-use crate::seeder::Seeder;
-use clap::{Parser, Subcommand};
-use console::style;
+use db_seeder::config::{AppConfig, DatabaseConfig, Rows, SeedingTask};
+use db_seeder::db::DbClient;
+use db_seeder::error::{AppError, AppResult};
+use db_seeder::gemini_analyzer::ArchitecturalPlan;
+use db_seeder::report::RunSummary;
+use db_seeder::seeder::{self, RunOptions, Seeder};
+use db_seeder::{init, interactive, interrupt, validator};
+use clap::{Parser, Subcommand, ValueEnum};
+use console::{style, Term};
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Утиліта для інтелектуального заповнення БД", long_about = None)]
@@ -22,6 +19,92 @@ struct Cli {
 
     #[arg(short, long, global = true, default_value = "config.toml")]
     config: String,
+
+    /// Завантажити змінні середовища з цього файлу замість `.env` у поточному каталозі
+    #[arg(long, global = true)]
+    env_file: Option<String>,
+
+    /// Заповнити лише вказані таблиці з плану (та їхніх батьків), через кому: users,orders
+    #[arg(long, global = true, value_delimiter = ',')]
+    tables: Option<Vec<String>>,
+
+    /// Порядок заповнення таблиць через кому замість автоматичного (як `generation.table_order`): users,orders
+    #[arg(long, global = true, value_delimiter = ',')]
+    table_order: Option<Vec<String>>,
+
+    /// Однакова кількість рядків для кожної таблиці плану (має пріоритет над --scale та конфігурацією)
+    #[arg(long, global = true)]
+    rows: Option<u32>,
+
+    /// Множник для кількості рядків з конфігурації, наприклад 0.1 або 10
+    #[arg(long, global = true)]
+    scale: Option<f64>,
+
+    /// Запобіжник: не більше N рядків у будь-якій таблиці, навіть після --rows чи --scale (як `generation.max_rows_per_table`)
+    #[arg(long, global = true)]
+    max_rows_per_table: Option<u32>,
+
+    /// Не питати підтвердження перед записом у БД (для CI та скриптів)
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// Формат логів: 'pretty' для людей, 'json' для машинної обробки
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
+    /// Показувати лише попередження та помилки
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Показувати детальні (debug) повідомлення
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Згенерувати пули даних заново, ігноруючи кеш з `gemini.pool_cache_dir`
+    #[arg(long, global = true)]
+    refresh_pools: bool,
+
+    /// Заповнити пули заглушками (`<pool_name>_1`, ...) без запитів до Gemini; план усе одно береться з Gemini або кешу
+    #[arg(long, global = true, conflicts_with = "refresh_pools")]
+    skip_pools: bool,
+
+    /// Використати збережений план (з `plan --output`) замість запиту до Gemini
+    #[arg(long, global = true)]
+    plan: Option<String>,
+
+    /// Вважати помилкою таблиці з плану, яких немає в схемі БД (замість попередження та пропуску)
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Обробити не більше N таблиць плану (перші в порядку заповнення, від коренів) для пробного запуску
+    #[arg(long, global = true)]
+    limit_tables: Option<usize>,
+
+    /// Записати машиночитний звіт (план, результати по таблицях, токени) у JSON-файл
+    #[arg(long, global = true)]
+    report_json: Option<String>,
+
+    /// Показати розподіл часу: план, пули, генерація та вставка по таблицях
+    #[arg(long, global = true)]
+    stats: bool,
+
+    /// Профіль плану з `[[seeding_plan.<profile>]]`, наприклад smoke або load (за замовчуванням default)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Не зупинятися на таблиці з помилкою: пропустити її та залежні від неї таблиці й продовжити
+    #[arg(long, global = true)]
+    continue_on_error: bool,
+
+    /// Заповнювати лише порожні таблиці: таблиці з даними не змінюються, а `fk` посилається на їхні наявні ключі
+    #[arg(long, global = true)]
+    seed_only_empty: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormat {
+    Pretty,
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
@@ -30,46 +113,448 @@ enum Commands {
     File,
     /// Запустити інтерактивний режим для вибору таблиць
     Interactive,
+    /// Перевірити узгодженість конфігурації та схеми БД (без Gemini та без запису); з --plan — ще й розбіжності збереженого плану зі схемою
+    Validate,
+    /// Отримати архітектурний план від Gemini, показати його та вийти без запису в БД
+    Plan {
+        /// Зберегти план у JSON-файл, щоб відредагувати його та передати через --plan
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Створити стартовий файл конфігурації (шлях з --config) за схемою БД
+    Init {
+        /// URL бази даних; за замовчуванням змінна середовища DATABASE_URL
+        #[arg(long)]
+        database_url: Option<String>,
+        /// Схема Postgres (за замовчуванням public)
+        #[arg(long)]
+        schema: Option<String>,
+        /// Кількість рядків для кожної таблиці в плані
+        #[arg(long, default_value_t = 10)]
+        default_rows: u32,
+        /// Перезаписати наявний файл конфігурації
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Налаштовує `tracing`. RUST_LOG, якщо заданий, має пріоритет над --quiet/--verbose.
+fn init_logging(cli: &Cli) {
+    let level = if cli.quiet {
+        "warn"
+    } else if cli.verbose {
+        "debug"
+    } else {
+        "info"
+    };
+    // Логи сторонніх бібліотек (sqlx, reqwest) показуємо лише від рівня warn
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(format!("warn,db_seeder={}", level)));
+
+    match cli.log_format {
+        LogFormat::Pretty => {
+            tracing_subscriber::fmt()
+                .with_env_filter(filter)
+                .without_time()
+                .with_target(false)
+                .with_level(false)
+                .init();
+        }
+        LogFormat::Json => {
+            // Кольорові ANSI-коди в JSON лише заважають
+            console::set_colors_enabled(false);
+            tracing_subscriber::fmt().with_env_filter(filter).json().with_ansi(false).init();
+        }
+    }
+}
+
+/// Застосовує --rows / --scale до плану. Пріоритет: --rows, потім --scale, потім значення з конфігурації.
+fn apply_row_overrides(plan: &mut [SeedingTask], cli: &Cli) {
+    if let Some(rows) = cli.rows {
+        info!("📏 Встановлюю {} рядків для кожної таблиці (--rows)", rows);
+        plan.iter_mut().for_each(|task| task.rows = Rows::Fixed(rows));
+    } else if let Some(scale) = cli.scale {
+        info!("📏 Масштабую кількість рядків у {} раз(и) (--scale)", scale);
+        // Відносна кількість ("3x orders") масштабується разом із батьківською таблицею
+        for task in plan.iter_mut() {
+            if let Rows::Fixed(rows) = task.rows {
+                let scaled = (rows as f64 * scale.max(0.0)).round() as u32;
+                // Таблиця, яку треба було заповнювати, не повинна "зникнути" через округлення
+                task.rows = Rows::Fixed(if rows > 0 { scaled.max(1) } else { 0 });
+            }
+        }
+    }
+}
+
+/// Просить підтвердження, якщо заповнення зачепить таблиці, що вже містять дані.
+/// Без TTY і без --yes відмовляємося, щоб не зависнути на запитанні в CI.
+async fn confirm_writes(seeder: &Seeder, plan: &[SeedingTask], assume_yes: bool) -> AppResult<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+
+    let non_empty = seeder.non_empty_tables(plan).await?;
+    if non_empty.is_empty() {
+        return Ok(true);
+    }
+
+    warn!("⚠️  Таблиці вже містять дані: {}", non_empty.join(", "));
+    let term = Term::stderr();
+    if !term.is_term() {
+        return Err(AppError::Custom(
+            "Потрібне підтвердження запису в непорожні таблиці, але термінал недоступний. Передайте --yes".to_string(),
+        ));
+    }
+
+    let confirmed = dialoguer::Confirm::new()
+        .with_prompt(format!("Це змінить {} таблиць, продовжити?", plan.len()))
+        .default(false)
+        .interact_on(&term)?;
+    Ok(confirmed)
+}
+
+/// Підключається до БД, читає схему та записує стартову конфігурацію в `path`
+async fn init_config(path: &str, database_url: Option<&str>, schema: Option<&str>, default_rows: u32, force: bool) -> AppResult<()> {
+    if std::path::Path::new(path).exists() && !force {
+        return Err(AppError::Custom(format!("Файл '{}' вже існує. Передайте --force, щоб перезаписати його", path)));
+    }
+
+    // URL з DATABASE_URL записуємо як посилання на змінну, щоб пароль не потрапив у файл
+    let (url, url_in_file) = match database_url {
+        Some(url) => (url.to_string(), url.to_string()),
+        None => match std::env::var("DATABASE_URL") {
+            Ok(url) => (url, "${DATABASE_URL}".to_string()),
+            Err(_) => {
+                return Err(AppError::Custom(
+                    "Не вказано базу даних: передайте --database-url або задайте DATABASE_URL".to_string(),
+                ))
+            }
+        },
+    };
+
+    info!("🔌 Підключаюся до бази даних...");
+    let db_config = DatabaseConfig {
+        url,
+        schema: schema.map(str::to_string),
+        ..DatabaseConfig::default()
+    };
+    let seeder = Seeder::new(DbClient::new(&db_config).await?).await?;
+
+    std::fs::write(path, init::starter_config(&seeder, &url_in_file, schema, default_rows))?;
+    info!("✅ Конфігурацію з {} таблицями записано в '{}'.", seeder.schema().tables.len(), path);
+    Ok(())
+}
+
+/// Частина `seeding_plan`, що заповнюється в одну базу даних
+struct DatabaseTarget {
+    /// Ім'я підключення з `[databases]`; `None` — основне `[database]`
+    name: Option<String>,
+    seeder: Seeder,
+    plan: Vec<SeedingTask>,
+}
+
+impl DatabaseTarget {
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or("database")
+    }
+}
+
+/// Розбиває `seeding_plan` за полем `database`, підключається до додаткових БД і застосовує --tables
+/// до кожної з них окремо, щоб батьківські таблиці бралися з тієї ж бази.
+async fn database_targets(
+    config: &AppConfig,
+    default_seeder: Seeder,
+    tables: Option<&[String]>,
+    limit_tables: Option<usize>,
+) -> AppResult<Vec<DatabaseTarget>> {
+    let plan = config.plan.as_deref().unwrap_or_default();
+
+    let mut unknown: Vec<&str> = plan
+        .iter()
+        .filter_map(|t| t.database.as_deref())
+        .filter(|name| !config.databases.contains_key(*name))
+        .collect();
+    unknown.sort_unstable();
+    unknown.dedup();
+    if !unknown.is_empty() {
+        return Err(AppError::Custom(format!(
+            "seeding_plan посилається на невідомі підключення: {}. Опишіть їх у секції [databases]",
+            unknown.join(", ")
+        )));
+    }
+
+    // Групи в порядку появи в плані; основна БД лишається, навіть якщо план порожній
+    let mut groups: Vec<(Option<String>, Vec<SeedingTask>)> = vec![(None, Vec::new())];
+    for task in plan {
+        match groups.iter_mut().find(|(name, _)| *name == task.database) {
+            Some((_, tasks)) => tasks.push(task.clone()),
+            None => groups.push((task.database.clone(), vec![task.clone()])),
+        }
+    }
+    if groups.len() > 1 && groups[0].1.is_empty() {
+        groups.remove(0);
+    }
+    check_cross_database_fks(&groups)?;
+
+    if let Some(tables) = tables {
+        let missing: Vec<&str> = tables
+            .iter()
+            .filter(|name| !plan.iter().any(|t| &t.table == *name))
+            .map(|s| s.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Custom(format!("Таблиці відсутні в seeding_plan: {}", missing.join(", "))));
+        }
+    }
+
+    let mut default_seeder = Some(default_seeder);
+    let mut targets = Vec::new();
+    for (name, tasks) in groups {
+        let wanted: Option<Vec<String>> = tables.map(|tables| {
+            tables.iter().filter(|t| tasks.iter().any(|task| &task.table == *t)).cloned().collect()
+        });
+        if wanted.as_ref().is_some_and(|w| w.is_empty()) {
+            continue;
+        }
+
+        let seeder = match &name {
+            None => default_seeder.take().expect("основна БД має лише одну групу"),
+            Some(name) => {
+                info!("🔌 Підключаюся до бази даних '{}'...", name);
+                Seeder::new(DbClient::new(&config.databases[name]).await?).await?
+            }
+        };
+        let mut plan = match wanted {
+            Some(wanted) => seeder.filter_plan(&tasks, &wanted)?,
+            None => tasks,
+        };
+        if let Some(limit) = limit_tables {
+            plan = seeder.limit_plan(&plan, limit);
+        }
+        targets.push(DatabaseTarget { name, seeder, plan });
+    }
+    Ok(targets)
+}
+
+/// `fk` з `column_overrides` не може посилатися на таблицю, що заповнюється в іншу базу даних
+fn check_cross_database_fks(groups: &[(Option<String>, Vec<SeedingTask>)]) -> AppResult<()> {
+    for (name, tasks) in groups {
+        for task in tasks {
+            for (column, column_override) in task.column_overrides.iter().filter(|(_, o)| o.generator == "fk") {
+                let Some(parent) = column_override.params.get("references").and_then(|v| v.as_str()) else { continue };
+                if tasks.iter().any(|t| t.table == parent) {
+                    continue;
+                }
+                if let Some((other, _)) = groups.iter().find(|(_, other_tasks)| other_tasks.iter().any(|t| t.table == parent)) {
+                    return Err(AppError::Custom(format!(
+                        "'{}.{}' ({}) посилається на таблицю '{}' з бази даних '{}': FK між різними базами даних не підтримуються",
+                        task.table,
+                        column,
+                        name.as_deref().unwrap_or("database"),
+                        parent,
+                        other.as_deref().unwrap_or("database")
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Виводить підсумок запуску. Якщо частину таблиць пропущено (`--continue-on-error`), завершуємося з помилкою,
+/// щоб скрипти та CI не вважали такий запуск повністю успішним
+fn finish(summary: RunSummary, report_json: Option<&str>, stats: bool) -> AppResult<()> {
+    summary.print();
+    if stats {
+        summary.print_stats();
+    }
+    if let Some(path) = report_json {
+        summary.write_json(path)?;
+    }
+    if summary.failed.is_empty() {
+        return Ok(());
+    }
+    let tables: Vec<&str> = summary.failed.iter().map(|f| f.table.as_str()).collect();
+    Err(AppError::Custom(format!("Не заповнено таблиці: {}", tables.join(", "))))
 }
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    if let Err(e) = dotenvy::dotenv() {
-        if !e.to_string().contains("No such file or directory") {
-            eprintln!("{} Помилка завантаження .env файлу: {}", style("[!]").yellow(), e);
+    let cli = Cli::parse();
+    init_logging(&cli);
+    interrupt::install_handler();
+
+    match run(cli).await {
+        Err(AppError::Interrupted) => {
+            warn!("🛑 Роботу перервано, незавершені зміни відкочено.");
+            std::process::exit(interrupt::EXIT_CODE);
+        }
+        Err(AppError::ValidationFailed(problems)) => {
+            error!("{}", style(format!("❌ Знайдено проблем: {}", problems.len())).red().bold());
+            for problem in &problems {
+                error!("   - {}", problem);
+            }
+            std::process::exit(1);
         }
+        result => result,
     }
+}
 
-    let cli = Cli::parse();
+async fn run(cli: Cli) -> AppResult<()> {
+
+    match &cli.env_file {
+        // Явно вказаний файл має існувати: інакше запуск мовчки піде з чужими обліковими даними
+        Some(path) => {
+            dotenvy::from_path(path).map_err(|e| AppError::Custom(format!("Не вдалося завантажити '{}': {}", path, e)))?;
+        }
+        None => {
+            if let Err(e) = dotenvy::dotenv() {
+                if !e.not_found() {
+                    warn!("{} Помилка завантаження .env файлу: {}", style("[!]").yellow(), e);
+                }
+            }
+        }
+    }
 
-    println!("⚙️  Завантажую конфігурацію з '{}'...", &cli.config);
+    // Для `init` конфігурації ще не існує, тож вона не завантажується
+    if let Commands::Init { database_url, schema, default_rows, force } = &cli.command {
+        return init_config(&cli.config, database_url.as_deref(), schema.as_deref(), *default_rows, *force).await;
+    }
+
+    info!("⚙️  Завантажую конфігурацію з '{}'...", &cli.config);
     // Робимо конфігурацію мутабельною, щоб можна було змінити `plan`
-    let mut config = AppConfig::from_file(&cli.config)?;
+    let mut config = AppConfig::from_file(&cli.config, cli.profile.as_deref())?;
+    if let Some(profile) = &cli.profile {
+        info!("📋 Профіль плану: {}", style(profile).bold());
+    }
 
-    println!("🔌 Підключаюся до бази даних...");
-    let db_client = DbClient::new(&config.database.url).await?;
-    println!("✅ Підключення успішне.");
+    info!("🔌 Підключаюся до бази даних...");
+    let db_client = DbClient::new(&config.database).await?;
+    info!("✅ Підключення успішне.");
 
     let seeder = Seeder::new(db_client).await?;
 
-    match cli.command {
+    let options = RunOptions {
+        refresh_pools: cli.refresh_pools,
+        skip_pools: cli.skip_pools,
+        plan_file: cli.plan.clone(),
+        continue_on_error: cli.continue_on_error,
+        seed_only_empty: cli.seed_only_empty,
+        strict: cli.strict,
+        table_order: cli.table_order.clone(),
+        max_rows_per_table: cli.max_rows_per_table,
+        ..RunOptions::default()
+    };
+
+    match &cli.command {
         Commands::File => {
-            println!("\n▶️  Режим: заповнення з файлу.");
-            seeder.run(&config).await?;
+            info!("▶️  Режим: заповнення з файлу.");
+            if let Some(plan) = config.plan.as_mut() {
+                apply_row_overrides(plan, &cli);
+            }
+            let targets = database_targets(&config, seeder, cli.tables.as_deref(), cli.limit_tables).await?;
+            for target in &targets {
+                if !confirm_writes(&target.seeder, &target.plan, cli.yes || cli.seed_only_empty).await? {
+                    info!("Скасовано користувачем.");
+                    return Ok(());
+                }
+            }
+
+            let multiple = targets.len() > 1;
+            let mut summary = RunSummary::default();
+            // Один клієнт Gemini на всі бази даних, щоб `gemini.max_api_calls` обмежував увесь запуск
+            let analyzer = seeder::create_analyzer(&config)?;
+            for target in targets {
+                if multiple {
+                    info!("🗄️  Заповнюю базу даних '{}'...", style(target.label()).bold());
+                }
+                config.plan = Some(target.plan);
+                let Some(architectural_plan) = target.seeder.architectural_plan(&config, &analyzer, &options).await? else {
+                    continue;
+                };
+                summary.merge(target.seeder.seed_plan(&config, &options, &analyzer, architectural_plan).await?);
+            }
+            finish(summary, cli.report_json.as_deref(), cli.stats)?;
         }
         Commands::Interactive => {
-            println!("\n▶️  Режим: інтерактивний.");
+            info!("▶️  Режим: інтерактивний.");
             let default_rows = config.default_rows.unwrap_or(10);
             
             // Викликаємо правильну функцію
             let graph = seeder.build_full_dependency_graph();
-            let plan = interactive::run_interactive_mode(seeder.schema(), &graph, default_rows)?;
+            let mut plan = interactive::run_interactive_mode(seeder.schema(), &graph, default_rows)?;
 
             if !plan.is_empty() {
+                apply_row_overrides(&mut plan, &cli);
+                if !confirm_writes(&seeder, &plan, cli.yes || cli.seed_only_empty).await? {
+                    info!("Скасовано користувачем.");
+                    return Ok(());
+                }
                 // Оновлюємо план в існуючій конфігурації
                 config.plan = Some(plan);
-                seeder.run(&config).await?;
+                let analyzer = seeder::create_analyzer(&config)?;
+                let Some(architectural_plan) = seeder.architectural_plan(&config, &analyzer, &options).await? else {
+                    return Ok(());
+                };
+
+                // Показуємо план до будь-якого запису, щоб невдалий план можна було відхилити
+                architectural_plan.print();
+                if !cli.yes
+                    && !dialoguer::Confirm::new()
+                        .with_prompt("Продовжити заповнення за цим планом?")
+                        .default(false)
+                        .interact_on(&Term::stderr())?
+                {
+                    info!("Скасовано користувачем.");
+                    return Ok(());
+                }
+                finish(seeder.seed_plan(&config, &options, &analyzer, architectural_plan).await?, cli.report_json.as_deref(), cli.stats)?;
+            }
+        }
+        Commands::Plan { output } => {
+            info!("▶️  Режим: лише план.");
+            let targets = database_targets(&config, seeder, cli.tables.as_deref(), cli.limit_tables).await?;
+            if output.is_some() && targets.len() > 1 {
+                return Err(AppError::Custom(
+                    "--output зберігає план лише для однієї бази даних. Оберіть таблиці однієї БД через --tables".to_string(),
+                ));
+            }
+            for target in targets {
+                config.plan = Some(target.plan);
+                let analyzer = seeder::create_analyzer(&config)?;
+                if let Some(plan) = target.seeder.architectural_plan(&config, &analyzer, &options).await? {
+                    plan.print();
+                    if let Some(path) = output {
+                        plan.save(path)?;
+                        info!("💾 План збережено в '{}'. Використайте його через --plan {}", path, path);
+                    }
+                }
+            }
+        }
+        Commands::Init { .. } => unreachable!("команда init обробляється до завантаження конфігурації"),
+        Commands::Validate => {
+            info!("▶️  Режим: перевірка конфігурації.");
+            let targets = database_targets(&config, seeder, cli.tables.as_deref(), cli.limit_tables).await?;
+            let multiple = targets.len() > 1;
+            // Збережений план перевіряємо на розбіжності зі схемою, що могла змінитися після `plan --output`
+            let saved_plan = cli.plan.as_deref().map(ArchitecturalPlan::load).transpose()?;
+            let mut problems = Vec::new();
+            for target in &targets {
+                let graph = target.seeder.build_full_dependency_graph();
+                let populated_parents = target.seeder.populated_parents(&target.plan).await?;
+                let mut target_problems = validator::validate_plan(target.seeder.schema(), &graph, &target.plan, &populated_parents);
+                if let Some(saved_plan) = &saved_plan {
+                    target_problems.extend(validator::plan_drift(target.seeder.schema(), saved_plan, &target.plan));
+                }
+                for problem in target_problems {
+                    problems.push(if multiple { format!("[{}] {}", target.label(), problem) } else { problem });
+                }
+            }
+
+            if !problems.is_empty() {
+                return Err(AppError::ValidationFailed(problems));
             }
+            let table_count: usize = targets.iter().map(|t| t.plan.len()).sum();
+            info!("✅ Конфігурація узгоджена зі схемою БД ({} таблиць у плані).", table_count);
         }
     }
 
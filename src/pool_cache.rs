@@ -0,0 +1,44 @@
+// src/pool_cache.rs
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tracing::{debug, warn};
+
+use crate::error::AppResult;
+
+/// Дисковий кеш пулів даних. Ключ — SHA-256 від `gemini_prompt_for_pool`,
+/// тож зміна промпту автоматично призводить до повторної генерації пулу.
+pub struct PoolCache {
+    dir: PathBuf,
+}
+
+impl PoolCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, prompt: &str) -> PathBuf {
+        let hash = Sha256::digest(prompt.as_bytes());
+        self.dir.join(format!("{:x}.json", hash))
+    }
+
+    /// Повертає збережені значення пулу, якщо кеш для цього промпту існує і читається
+    pub fn load(&self, prompt: &str) -> Option<Vec<String>> {
+        let path = self.path_for(prompt);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(values) => Some(values),
+            Err(e) => {
+                warn!("Пошкоджений файл кешу '{}', пул буде згенеровано заново: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn store(&self, prompt: &str, values: &[String]) -> AppResult<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(prompt);
+        std::fs::write(&path, serde_json::to_string_pretty(values)?)?;
+        debug!("Пул збережено в кеш '{}'", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+// src/report.rs
+use crate::error::{AppError, AppResult};
+use crate::gemini_analyzer::ArchitecturalPlan;
+use console::style;
+use serde_json::json;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Результат заповнення однієї таблиці
+#[derive(Debug, Clone)]
+pub struct TableReport {
+    pub table: String,
+    /// Скільки рядків було згенеровано та відправлено в БД
+    pub attempted: u64,
+    /// Скільки рядків фактично вставлено (підтверджено `RETURNING` / `rows_affected`)
+    pub inserted: u64,
+    /// Приріст рядків у таблиці за даними `SELECT COUNT(*)` до та після заповнення
+    pub verified: Option<u64>,
+    pub elapsed: Duration,
+    /// Час генерації значень рядків (без очікування БД)
+    pub generation: Duration,
+    /// Час виконання INSERT (та UPDATE для самопосилань) у БД
+    pub insertion: Duration,
+}
+
+impl TableReport {
+    /// Рядки, які БД не вставила (наприклад, через ON CONFLICT DO NOTHING)
+    pub fn skipped(&self) -> u64 {
+        self.attempted.saturating_sub(self.inserted)
+    }
+
+    /// Чи відрізняється фактична кількість рядків від запитаної більше ніж на `threshold` (частка)
+    pub fn count_mismatch(&self, threshold: f64) -> bool {
+        let actual = self.verified.unwrap_or(self.inserted);
+        if self.attempted == 0 {
+            return actual != 0;
+        }
+        let diff = self.attempted.abs_diff(actual) as f64 / self.attempted as f64;
+        diff > threshold
+    }
+}
+
+/// Таблиця, яку не вдалося заповнити в режимі `--continue-on-error`
+#[derive(Debug, Clone)]
+pub struct TableFailure {
+    pub table: String,
+    /// Текст помилки або причина пропуску (наприклад, не заповнена батьківська таблиця)
+    pub reason: String,
+}
+
+/// Споживання токенів Gemini (за даними `usageMetadata`)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokenUsage {
+    /// Кількість запитів до API (включно з невдалими)
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Орієнтовна вартість за цінами в доларах за мільйон вхідних та вихідних токенів
+    pub fn estimated_cost(&self, input_price_per_million: f64, output_price_per_million: f64) -> f64 {
+        (self.prompt_tokens as f64 * input_price_per_million + self.output_tokens as f64 * output_price_per_million) / 1_000_000.0
+    }
+}
+
+/// Час, витрачений на запити до Gemini (включно з повторними спробами)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ApiTimings {
+    /// Отримання архітектурного плану
+    pub plan: Duration,
+    /// Запити даних для пулів
+    pub pools: Duration,
+}
+
+/// Підсумок усього запуску
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    pub tables: Vec<TableReport>,
+    /// Таблиці, пропущені через помилку (лише з `--continue-on-error`)
+    pub failed: Vec<TableFailure>,
+    pub elapsed: Duration,
+    pub tokens: TokenUsage,
+    pub api_timings: ApiTimings,
+    /// Заповнення пулів загалом: запити до Gemini, кеш та локальна генерація
+    pub pools_elapsed: Duration,
+    /// Орієнтовна вартість запитів до Gemini, якщо в конфігурації задано ціни
+    pub estimated_cost: Option<f64>,
+    /// Ліміт запитів `gemini.max_api_calls`, якщо задано
+    pub api_call_budget: Option<u64>,
+    /// Таблиці, для яких після заповнення виконано `ANALYZE` (`generation.analyze_after`)
+    pub analyzed: Vec<String>,
+    /// Чи це був `VACUUM ANALYZE` (`generation.vacuum_analyze`)
+    pub vacuumed: bool,
+    /// Архітектурні плани, за якими заповнювалися БД (по одному на кожну базу даних)
+    pub plans: Vec<ArchitecturalPlan>,
+}
+
+impl RunSummary {
+    /// Додає результати запуску для іншої бази даних до загального підсумку. Усі бази даних запуску
+    /// заповнюються через один `GeminiAnalyzer`, тож токени, час запитів і вартість у `other` — уже
+    /// накопичені за весь запуск і замінюють попередні, а ліміт `max_api_calls` — один на всі БД
+    pub fn merge(&mut self, other: RunSummary) {
+        self.tables.extend(other.tables);
+        self.failed.extend(other.failed);
+        self.plans.extend(other.plans);
+        self.analyzed.extend(other.analyzed);
+        self.vacuumed |= other.vacuumed;
+        self.elapsed += other.elapsed;
+        self.pools_elapsed += other.pools_elapsed;
+        self.api_timings = other.api_timings;
+        self.tokens = other.tokens;
+        self.estimated_cost = other.estimated_cost;
+        self.api_call_budget = other.api_call_budget;
+    }
+
+    pub fn total_inserted(&self) -> u64 {
+        self.tables.iter().map(|t| t.inserted).sum()
+    }
+
+    /// Машиночитний підсумок для CI та дашбордів: плани, результати по таблицях, час і токени
+    pub fn to_json(&self) -> serde_json::Value {
+        let tables: Vec<_> = self
+            .tables
+            .iter()
+            .map(|t| {
+                json!({
+                    "table": t.table,
+                    "requested": t.attempted,
+                    "inserted": t.inserted,
+                    "skipped": t.skipped(),
+                    "verified": t.verified,
+                    "elapsed_secs": t.elapsed.as_secs_f64(),
+                    "generation_secs": t.generation.as_secs_f64(),
+                    "insertion_secs": t.insertion.as_secs_f64(),
+                })
+            })
+            .collect();
+        let failed: Vec<_> = self.failed.iter().map(|f| json!({ "table": f.table, "reason": f.reason })).collect();
+        json!({
+            "plans": self.plans,
+            "tables": tables,
+            "failed": failed,
+            "total_inserted": self.total_inserted(),
+            "elapsed_secs": self.elapsed.as_secs_f64(),
+            "timings": {
+                "plan_secs": self.api_timings.plan.as_secs_f64(),
+                "pools_secs": self.pools_elapsed.as_secs_f64(),
+                "pool_requests_secs": self.api_timings.pools.as_secs_f64(),
+            },
+            "tokens": {
+                "requests": self.tokens.requests,
+                "prompt_tokens": self.tokens.prompt_tokens,
+                "output_tokens": self.tokens.output_tokens,
+            },
+            "estimated_cost": self.estimated_cost,
+            "api_call_budget": self.api_call_budget,
+            "analyzed": self.analyzed,
+        })
+    }
+
+    /// Записує `to_json` у файл
+    pub fn write_json(&self, path: &str) -> AppResult<()> {
+        let content = serde_json::to_string_pretty(&self.to_json())?;
+        std::fs::write(path, content).map_err(|e| AppError::Custom(format!("Не вдалося записати звіт у '{}': {}", path, e)))?;
+        info!("📝 Звіт збережено у '{}'", path);
+        Ok(())
+    }
+
+    /// Виводить таблицю з підсумками по кожній таблиці та споживання токенів
+    pub fn print(&self) {
+        self.print_tables();
+        self.print_analyzed();
+        self.print_failures();
+        self.print_token_usage();
+    }
+
+    /// Розподіл часу запуску (`--stats`): план, пули, генерація та вставка по таблицях
+    pub fn print_stats(&self) {
+        let generation: Duration = self.tables.iter().map(|t| t.generation).sum();
+        let insertion: Duration = self.tables.iter().map(|t| t.insertion).sum();
+        let total = self.api_timings.plan + self.elapsed;
+        let name_width = self.tables.iter().map(|t| t.table.len()).max().unwrap_or(0).max("  з них запити до Gemini".chars().count());
+
+        info!("⏱️  Розподіл часу:");
+        info!("   {:<name_width$}  {:>10}  {:>10}  {:>10}", "Етап", "Генерація", "Вставка", "Разом");
+        info!("   {:<name_width$}  {:>10}  {:>10}  {:>9.2}s", "План (Gemini)", "-", "-", self.api_timings.plan.as_secs_f64());
+        info!("   {:<name_width$}  {:>10}  {:>10}  {:>9.2}s", "Пули", "-", "-", self.pools_elapsed.as_secs_f64());
+        info!("   {:<name_width$}  {:>10}  {:>10}  {:>9.2}s", "  з них запити до Gemini", "-", "-", self.api_timings.pools.as_secs_f64());
+        for report in &self.tables {
+            info!(
+                "   {:<name_width$}  {:>9.2}s  {:>9.2}s  {:>9.2}s",
+                report.table,
+                report.generation.as_secs_f64(),
+                report.insertion.as_secs_f64(),
+                report.elapsed.as_secs_f64()
+            );
+        }
+        info!(
+            "   {:<name_width$}  {:>9.2}s  {:>9.2}s  {:>9.2}s",
+            "Разом",
+            generation.as_secs_f64(),
+            insertion.as_secs_f64(),
+            total.as_secs_f64()
+        );
+    }
+
+    fn print_analyzed(&self) {
+        if self.analyzed.is_empty() {
+            return;
+        }
+        let command = if self.vacuumed { "VACUUM ANALYZE" } else { "ANALYZE" };
+        info!("🧮 Статистику оновлено ({}) для {} таблиць: {}", command, self.analyzed.len(), self.analyzed.join(", "));
+    }
+
+    fn print_failures(&self) {
+        if self.failed.is_empty() {
+            return;
+        }
+        warn!("{}", style(format!("❌ Не заповнено таблиць: {}", self.failed.len())).red().bold());
+        for failure in &self.failed {
+            warn!("   - {}: {}", failure.table, failure.reason);
+        }
+    }
+
+    fn print_tables(&self) {
+        if self.tables.is_empty() {
+            return;
+        }
+
+        let name_width = self.tables.iter().map(|t| t.table.len()).max().unwrap_or(0).max("Таблиця".chars().count());
+        info!("📊 Підсумок заповнення:");
+        info!("   {:<name_width$}  {:>10}  {:>10}  {:>10}  {:>10}  {:>9}", "Таблиця", "Спроб", "Вставлено", "Пропущено", "У БД", "Час");
+        for report in &self.tables {
+            let skipped = report.skipped();
+            let skipped_str = format!("{:>10}", skipped);
+            info!(
+                "   {:<name_width$}  {:>10}  {:>10}  {}  {:>10}  {:>8.2}s",
+                report.table,
+                report.attempted,
+                report.inserted,
+                if skipped > 0 { style(skipped_str).yellow() } else { style(skipped_str) },
+                report.verified.map_or("-".to_string(), |v| v.to_string()),
+                report.elapsed.as_secs_f64()
+            );
+        }
+        info!(
+            "   Разом: {} рядків у {} таблицях за {:.2}s",
+            self.total_inserted(),
+            self.tables.len(),
+            self.elapsed.as_secs_f64()
+        );
+    }
+
+    fn print_token_usage(&self) {
+        if self.tokens.requests == 0 {
+            return;
+        }
+        let requests = match self.api_call_budget {
+            Some(budget) => format!("{} з {}", self.tokens.requests, budget),
+            None => self.tokens.requests.to_string(),
+        };
+        info!(
+            "🪙 Gemini: {} запитів, {} вхідних і {} вихідних токенів",
+            requests, self.tokens.prompt_tokens, self.tokens.output_tokens
+        );
+        if let Some(cost) = self.estimated_cost {
+            info!("   Орієнтовна вартість: ${:.4}", cost);
+        }
+    }
+}
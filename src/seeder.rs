@@ -1,27 +1,124 @@
 // src/seeder.rs
-use crate::config::SeedingTask;
-use crate::db::{DbClient, DbSchema};
-use crate::entity_generator::{DataPools, EntityGenerator};
+use crate::config::{Rows, SeedingTask, TransactionMode, DEFAULT_EXISTING_PKS_LIMIT, DEFAULT_ROW_RETRIES, DEFAULT_TRANSIENT_RETRIES};
+use crate::db::{ColumnSchema, DbClient, DbSchema, TableSchema};
+use crate::entity_generator::{from_query_key, order_fields, referenced_fields, DataPools, EntityGenerator, GeneratorFn, GeneratorRegistry, TableState};
 use crate::error::{AppError, AppResult};
-use crate::gemini_analyzer::GeminiAnalyzer;
+use crate::gemini_analyzer::{ArchitecturalPlan, DataPoolConfig, EntityTemplate, FieldTemplate, GeminiAnalyzer};
+use crate::interrupt;
+use crate::lorem;
+use crate::pool_cache::PoolCache;
+use crate::report::{RunSummary, TableFailure, TableReport};
+use crate::validator;
+use base64::prelude::*;
 use console::style;
-use indicatif::ProgressBar;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use petgraph::algo::toposort;
 use petgraph::graphmap::DiGraphMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde_json::{json, Value};
-use sqlx::Row;
-use std::collections::{HashMap, HashSet};
+use sqlx::postgres::PgArguments;
+use sqlx::pool::PoolConnection;
+use sqlx::{Connection, PgConnection, Postgres, Row, Transaction};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Скільки значень генерувати для пулу, заповненого локально (`gemini.prefer_local_pools`, `--skip-pools`)
+const LOCAL_POOL_SIZE: usize = 100;
+
+/// Домішується до зерна таблиці для `rows_jitter`, щоб кількість рядків не бралася з того ж потоку,
+/// що й перше значення генератора таблиці
+const ROWS_JITTER_SALT: u64 = 0x6a09_e667_f3bc_c909;
+
+type PgQuery<'q> = sqlx::query::Query<'q, Postgres, PgArguments>;
+
+/// Параметри запуску, що приходять з командного рядка, а не з файлу конфігурації
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    /// Ігнорувати кеш пулів і згенерувати їх заново (кеш при цьому оновлюється)
+    pub refresh_pools: bool,
+    /// Заповнювати пули заглушками `<pool_name>_N` без запитів до Gemini (для перевірки структури та порядку)
+    pub skip_pools: bool,
+    /// Збережений архітектурний план (JSON), який використовується замість запиту до Gemini
+    pub plan_file: Option<String>,
+    /// Готовий архітектурний план (для використання як бібліотеки); має пріоритет над `plan_file`
+    pub architectural_plan: Option<ArchitecturalPlan>,
+    /// Пропускати таблицю, що завершилася помилкою (і залежні від неї), замість зупинки всього запуску
+    pub continue_on_error: bool,
+    /// Пропускати таблиці, що вже містять дані; `fk` дочірніх таблиць посилаються на їхні наявні ключі
+    pub seed_only_empty: bool,
+    /// Завершувати запуск помилкою, якщо таблиць з плану немає в схемі БД (без нього — лише попередження)
+    pub strict: bool,
+    /// Ручний порядок заповнення таблиць (має пріоритет над `generation.table_order`)
+    pub table_order: Option<Vec<String>>,
+    /// Найбільша кількість рядків для будь-якої таблиці (має пріоритет над `generation.max_rows_per_table`)
+    pub max_rows_per_table: Option<u32>,
+}
+
+/// Загальний прогрес запуску (усі рядки всіх таблиць) з прогресом поточної таблиці під ним
+struct RunProgress {
+    multi: MultiProgress,
+    total: ProgressBar,
+}
+
+impl RunProgress {
+    fn new(total_rows: u64) -> AppResult<Self> {
+        let multi = MultiProgress::new();
+        let total = multi.add(ProgressBar::new(total_rows));
+        total.set_style(ProgressStyle::with_template("Усього [{wide_bar}] {human_pos}/{human_len} рядків ({eta})")?);
+        Ok(Self { multi, total })
+    }
+
+    fn table_bar(&self, table: &str, rows: u64) -> AppResult<ProgressBar> {
+        let bar = self.multi.add(ProgressBar::new(rows));
+        bar.set_style(ProgressStyle::with_template("{prefix} [{wide_bar}] {human_pos}/{human_len}")?);
+        bar.set_prefix(table.to_string());
+        Ok(bar)
+    }
+}
+
+/// Скільки разів повторювати вставку рядка та заповнення таблиці після відновлюваних помилок
+struct RetryPolicy {
+    row_retries: u32,
+    transient_retries: u32,
+}
+
+/// З'єднання, через яке йде заповнення. У режимі `global` уся робота відбувається всередині
+/// однієї транзакції, яка відкочується автоматично, якщо її не закомітили (помилка, Ctrl-C).
+enum RunConnection {
+    PerTable(PoolConnection<Postgres>),
+    Global(Transaction<'static, Postgres>),
+}
+
+impl RunConnection {
+    fn as_conn(&mut self) -> &mut PgConnection {
+        match self {
+            RunConnection::PerTable(conn) => conn,
+            RunConnection::Global(tx) => tx,
+        }
+    }
+}
 
 pub struct Seeder {
     db_client: DbClient,
     schema: DbSchema,
+    /// Користувацькі генератори, зареєстровані через `register_generator`
+    generators: GeneratorRegistry,
 }
 
 impl Seeder {
     pub async fn new(db_client: DbClient) -> AppResult<Self> {
-        println!("🔎 Читаю схему бази даних...");
+        info!("🔎 Читаю схему бази даних...");
         let schema = db_client.fetch_schema().await?;
-        Ok(Self { db_client, schema })
+        Ok(Self { db_client, schema, generators: GeneratorRegistry::new() })
+    }
+
+    /// Реєструє користувацький генератор (наприклад, `invoice_number`) для використання в плані
+    /// та `column_overrides`. Генератор з назвою вбудованого (наприклад, `words`) замінює його.
+    pub fn register_generator(&mut self, name: &str, generator: impl GeneratorFn + 'static) {
+        self.generators.insert(name.to_string(), Arc::new(generator));
     }
 
     // Метод для публічного доступу (для інтерактивного режиму)
@@ -36,6 +133,10 @@ impl Seeder {
             graph.add_node(table_name.as_str());
         }
         for fk in &self.schema.foreign_keys {
+            // Самопосилання (parent_id -> id) не є залежністю між таблицями
+            if fk.from_table == fk.to_table {
+                continue;
+            }
             if self.schema.tables.contains_key(&fk.from_table) && self.schema.tables.contains_key(&fk.to_table) {
                 graph.add_edge(fk.to_table.as_str(), fk.from_table.as_str(), ());
             }
@@ -43,6 +144,109 @@ impl Seeder {
         graph
     }
 
+    /// Залишає в плані лише вказані таблиці та всіх їхніх батьків із плану, щоб FK могли бути заповнені
+    pub fn filter_plan(&self, plan: &[SeedingTask], tables: &[String]) -> AppResult<Vec<SeedingTask>> {
+        let missing: Vec<&str> = tables
+            .iter()
+            .filter(|name| !plan.iter().any(|t| &t.table == *name))
+            .map(|s| s.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Custom(format!("Таблиці відсутні в seeding_plan: {}", missing.join(", "))));
+        }
+
+        let graph = self.build_full_dependency_graph();
+        let mut selected: HashSet<&str> = HashSet::new();
+        let mut to_visit: Vec<&str> = tables.iter().map(|s| s.as_str()).collect();
+        while let Some(current) = to_visit.pop() {
+            if !selected.insert(current) || !graph.contains_node(current) {
+                continue;
+            }
+            to_visit.extend(graph.neighbors_directed(current, petgraph::Direction::Incoming));
+        }
+
+        let filtered: Vec<SeedingTask> = plan.iter().filter(|t| selected.contains(t.table.as_str())).cloned().collect();
+        let added_parents: Vec<&str> = filtered
+            .iter()
+            .map(|t| t.table.as_str())
+            .filter(|name| !tables.iter().any(|t| t == name))
+            .collect();
+        if !added_parents.is_empty() {
+            info!("🔗 Додаю батьківські таблиці з плану: {}", added_parents.join(", "));
+        }
+        Ok(filtered)
+    }
+
+    /// Залишає в плані перші `limit` таблиць у порядку заповнення (від коренів), тож батьки вибраних таблиць
+    /// теж залишаються. Серед готових до заповнення таблиць перевага за порядком у плані.
+    pub fn limit_plan(&self, plan: &[SeedingTask], limit: usize) -> Vec<SeedingTask> {
+        if plan.len() <= limit {
+            return plan.to_vec();
+        }
+        let graph = self.build_plan_dependency_graph(plan);
+        let position = |table: &str| plan.iter().position(|t| t.table == table).unwrap_or(usize::MAX);
+
+        let mut remaining_parents: HashMap<&str, usize> =
+            graph.nodes().map(|n| (n, graph.neighbors_directed(n, petgraph::Direction::Incoming).count())).collect();
+        let mut ready: BTreeSet<(usize, &str)> =
+            remaining_parents.iter().filter(|(_, &count)| count == 0).map(|(&n, _)| (position(n), n)).collect();
+        let mut selected: HashSet<&str> = HashSet::new();
+        while selected.len() < limit {
+            let Some((_, table)) = ready.pop_first() else { break };
+            selected.insert(table);
+            for child in graph.neighbors_directed(table, petgraph::Direction::Outgoing) {
+                let count = remaining_parents.get_mut(child).expect("усі вузли графа порахувано");
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert((position(child), child));
+                }
+            }
+        }
+
+        let skipped: Vec<&str> = plan.iter().map(|t| t.table.as_str()).filter(|t| !selected.contains(t)).collect();
+        warn!(
+            "✂️  --limit-tables {}: пропускаю {} таблиць: {}",
+            limit,
+            skipped.len(),
+            style(skipped.join(", ")).yellow()
+        );
+        plan.iter().filter(|t| selected.contains(t.table.as_str())).cloned().collect()
+    }
+
+    /// Повертає таблиці з плану, які вже містять дані
+    pub async fn non_empty_tables(&self, plan: &[SeedingTask]) -> AppResult<Vec<String>> {
+        let mut conn = self.db_client.pool().acquire().await?;
+        let mut non_empty = Vec::new();
+        for task in plan.iter().filter(|t| self.schema.tables.contains_key(&t.table)) {
+            if self.db_client.table_has_rows(&mut conn, &task.table).await? {
+                non_empty.push(task.table.clone());
+            }
+        }
+        Ok(non_empty)
+    }
+
+    /// Батьківські таблиці поза планом, на які посилаються таблиці плану і які вже містять дані
+    pub async fn populated_parents(&self, plan: &[SeedingTask]) -> AppResult<BTreeSet<String>> {
+        let tables_in_plan: HashSet<&str> = plan.iter().map(|t| t.table.as_str()).collect();
+        let candidates: BTreeSet<&str> = self
+            .schema
+            .foreign_keys
+            .iter()
+            .filter(|fk| tables_in_plan.contains(fk.from_table.as_str()) && !tables_in_plan.contains(fk.to_table.as_str()))
+            .map(|fk| fk.to_table.as_str())
+            .filter(|table| self.schema.tables.contains_key(*table))
+            .collect();
+
+        let mut conn = self.db_client.pool().acquire().await?;
+        let mut populated = BTreeSet::new();
+        for table in candidates {
+            if self.db_client.table_has_rows(&mut conn, table).await? {
+                populated.insert(table.to_string());
+            }
+        }
+        Ok(populated)
+    }
+
     // Ця функція використовується всередині `run`
     fn build_plan_dependency_graph<'a>(&'a self, plan_tasks: &'a [SeedingTask]) -> DiGraphMap<&'a str, ()> {
         let mut graph = DiGraphMap::new();
@@ -58,219 +262,1487 @@ impl Seeder {
             let parent_table = fk.to_table.as_str();
             let child_table = fk.from_table.as_str();
 
+            // Самопосилання заповнюються другим проходом у `seed_table`, тож не створюють циклу
+            if parent_table == child_table {
+                continue;
+            }
+
             if tables_in_plan.contains(parent_table) && tables_in_plan.contains(child_table) {
                 // Дитина (from_table) залежить від батька (to_table)
                 graph.add_edge(parent_table, child_table, ());
             }
         }
+
+        // `rows = "3x orders"` рахується від уже заповненої `orders`, навіть без FK між таблицями
+        for task in plan_tasks {
+            if let Some(parent) = task.rows.parent().filter(|p| tables_in_plan.contains(p) && *p != task.table) {
+                graph.add_edge(parent, task.table.as_str(), ());
+            }
+        }
         graph
     }
 
-    pub async fn run(&self, config: &crate::config::AppConfig) -> AppResult<()> {
-        let gemini_key = std::env::var("GEMINI_API_KEY")
-            .map_err(|_| AppError::Custom("Змінна середовища GEMINI_API_KEY не встановлена".to_string()))?;
-        
-        let model = config.gemini.as_ref().map_or("gemini-1.5-flash-latest".to_string(), |g| g.model.clone());
-        let temperature = config.gemini.as_ref().and_then(|g| g.temperature).unwrap_or(0.7);
+    /// Отримує архітектурний план (від Gemini або з файлу `--plan`) і застосовує до нього
+    /// `column_overrides` та обмеження схеми. `None`, якщо в плані немає жодної таблиці зі схеми.
+    pub async fn architectural_plan(
+        &self,
+        config: &crate::config::AppConfig,
+        analyzer: &GeminiAnalyzer,
+        options: &RunOptions,
+    ) -> AppResult<Option<ArchitecturalPlan>> {
         let lang = config.generation.as_ref().map_or("en", |g| &g.language);
-        
-        let analyzer = GeminiAnalyzer::new(gemini_key, model, temperature);
-
-        println!("🧠 Gemini розробляє архітектурний план (мова: {})...", lang);
-        
         let plan_tasks = config.plan.as_ref().ok_or_else(|| AppError::Custom("Секція [[plan]] відсутня в конфігурації".to_string()))?;
 
+        // Перевіряємо до запиту плану в Gemini, щоб не витрачати квоту на заздалегідь невдалий запуск
+        let unreferenceable = validator::unreferenceable_parents(&self.schema, plan_tasks);
+        if !unreferenceable.is_empty() {
+            return Err(AppError::Custom(unreferenceable.join("; ")));
+        }
+        let invalid_rows = validator::invalid_relative_rows(plan_tasks);
+        if !invalid_rows.is_empty() {
+            return Err(AppError::Custom(invalid_rows.join("; ")));
+        }
+        let self_references = validator::required_self_references(&self.schema, plan_tasks);
+        if !self_references.is_empty() {
+            return Err(AppError::Custom(self_references.join("; ")));
+        }
+
+        let missing = validator::missing_tables(&self.schema, plan_tasks);
+        if !missing.is_empty() {
+            let message = format!("Таблиць з плану немає в схемі БД: {}", missing.join(", "));
+            if options.strict {
+                return Err(AppError::Custom(message));
+            }
+            warn!("⚠️  {}. Їх буде пропущено", style(message).yellow());
+        }
+
         let all_table_names: HashSet<&str> = plan_tasks.iter().map(|t| t.table.as_str()).collect();
         let schemas_for_analysis: Vec<_> = self.schema.tables.values().filter(|t| all_table_names.contains(t.name.as_str())).collect();
         if schemas_for_analysis.is_empty() {
-            println!("{}", style("Не знайдено таблиць для аналізу в схемі БД. Перевірте `plan` в конфігурації.").yellow());
-            return Ok(());
+            warn!("{}", style("Не знайдено таблиць для аналізу в схемі БД. Перевірте `plan` в конфігурації.").yellow());
+            return Ok(None);
+        }
+
+        let mut architectural_plan = match (&options.architectural_plan, &options.plan_file) {
+            (Some(plan), _) => plan.clone(),
+            (None, Some(path)) => {
+                info!("📄 Використовую збережений план з '{}'", path);
+                ArchitecturalPlan::load(path)?
+            }
+            (None, None) => {
+                info!("🧠 Gemini розробляє архітектурний план (мова: {})...", lang);
+
+                // Лише зв'язки між таблицями плану та з заповненими таблицями поза ним: на інші `fk` не зможе послатися
+                let populated_parents = self.populated_parents(plan_tasks).await?;
+                let foreign_keys_for_analysis: Vec<_> = self
+                    .schema
+                    .foreign_keys
+                    .iter()
+                    .filter(|fk| all_table_names.contains(fk.from_table.as_str()))
+                    .filter(|fk| all_table_names.contains(fk.to_table.as_str()) || populated_parents.contains(&fk.to_table))
+                    .collect();
+
+                let plan = analyzer.get_architectural_plan(&schemas_for_analysis, &foreign_keys_for_analysis, lang).await?;
+                if interrupt::is_requested() {
+                    return Err(AppError::Interrupted);
+                }
+                plan
+            }
+        };
+        info!("✅ План отримано! Тема: {}", style(&architectural_plan.theme).green());
+        let prefer_db_defaults = config.generation.as_ref().is_some_and(|g| g.prefer_db_defaults);
+        self.drop_fields_with_db_defaults(&mut architectural_plan, plan_tasks, prefer_db_defaults);
+        apply_column_overrides(&mut architectural_plan, plan_tasks)?;
+        self.adapt_templates_to_schema(&mut architectural_plan);
+        let dangling = validator::dangling_pool_references(&architectural_plan);
+        if !dangling.is_empty() {
+            return Err(AppError::Custom(format!("Архітектурний план неузгоджений: {}", dangling.join("; "))));
+        }
+        let drift = validator::plan_drift(&self.schema, &architectural_plan, plan_tasks);
+        if !drift.is_empty() {
+            warn!("{}", style(format!("⚠️  План розходиться зі схемою БД ({} проблем), вставка може завершитися помилкою:", drift.len())).yellow());
+            for problem in &drift {
+                warn!("   - {}", problem);
+            }
+        }
+        Ok(Some(architectural_plan))
+    }
+
+    /// Отримує план і одразу заповнює за ним БД
+    pub async fn run(&self, config: &crate::config::AppConfig, options: &RunOptions) -> AppResult<RunSummary> {
+        let analyzer = create_analyzer(config)?;
+        let Some(architectural_plan) = self.architectural_plan(config, &analyzer, options).await? else {
+            return Ok(RunSummary::default());
+        };
+        self.seed_plan(config, options, &analyzer, architectural_plan).await
+    }
+
+    /// Заповнює БД за вже отриманим планом (з `architectural_plan`), наприклад після підтвердження
+    /// користувачем. `analyzer` той самий, що отримав план, щоб підсумок врахував усі запити до Gemini.
+    pub async fn seed_plan(
+        &self,
+        config: &crate::config::AppConfig,
+        options: &RunOptions,
+        analyzer: &GeminiAnalyzer,
+        architectural_plan: ArchitecturalPlan,
+    ) -> AppResult<RunSummary> {
+        let started = Instant::now();
+        let mut summary = RunSummary::default();
+        let lang = config.generation.as_ref().map_or("en", |g| &g.language);
+        let mismatch_threshold = config.generation.as_ref().map_or(0.0, |g| g.count_mismatch_threshold);
+        let redacted_columns = config.generation.as_ref().map_or(&[][..], |g| g.redact_columns.as_slice());
+        let global_seed = config.generation.as_ref().and_then(|g| g.seed);
+        let append = config.generation.as_ref().is_some_and(|g| g.append);
+        let existing_pks_limit = config.generation.as_ref().map_or(DEFAULT_EXISTING_PKS_LIMIT, |g| g.existing_pks_limit);
+        let mut retries = RetryPolicy {
+            row_retries: config.generation.as_ref().map_or(DEFAULT_ROW_RETRIES, |g| g.row_retries),
+            transient_retries: config.generation.as_ref().map_or(DEFAULT_TRANSIENT_RETRIES, |g| g.transient_retries),
+        };
+
+        // Кількість рядків з `rows_jitter` обираємо один раз, щоб пули, прогрес і заповнення її узгоджували.
+        // Відносна кількість (`rows = "3x orders"`) тут лише оцінюється і уточнюється після заповнення батьківської таблиці
+        let configured_tasks = config.plan.as_deref().unwrap_or_default();
+        let max_rows = options.max_rows_per_table.or_else(|| config.generation.as_ref().and_then(|g| g.max_rows_per_table));
+        let plan_tasks = planned_rows(configured_tasks, global_seed, max_rows);
+        let plan_tasks = plan_tasks.as_slice();
+
+        // Унікальний `fk` на таблицю плану перевіряємо до будь-якої вставки; батьків поза планом — у `seed_table`
+        let shortages: Vec<String> = architectural_plan
+            .entity_templates
+            .iter()
+            .filter_map(|template| plan_tasks.iter().find(|t| t.table == template.target_table).map(|task| (template, task)))
+            .flat_map(|(template, task)| {
+                unique_fk_shortages(template, task.rows.count(), |parent| {
+                    plan_tasks.iter().find(|t| t.table == parent).map(|t| t.rows.count() as usize)
+                })
+            })
+            .collect();
+        if !shortages.is_empty() {
+            return Err(AppError::Custom(shortages.join("; ")));
         }
-        
-        let architectural_plan = analyzer.get_architectural_plan(&schemas_for_analysis, lang).await?;
-        println!("✅ План отримано! Тема: {}", style(&architectural_plan.theme).green());
 
         let mut data_pools = DataPools::new();
+        let pools_started = Instant::now();
+        let pool_cache = config.gemini.as_ref().and_then(|g| g.pool_cache_dir.as_deref()).map(PoolCache::new);
+        let prefer_local_pools = config.gemini.as_ref().is_some_and(|g| g.prefer_local_pools);
         if !architectural_plan.data_pools.is_empty() {
-            println!("💧 Заповнюю пули даних за допомогою Gemini...");
+            if options.skip_pools {
+                info!("💧 Заповнюю пули даних заглушками (--skip-pools)...");
+            } else {
+                info!("💧 Заповнюю пули даних за допомогою Gemini...");
+            }
             let bar = ProgressBar::new(architectural_plan.data_pools.len() as u64);
             for (pool_name, pool_config) in &architectural_plan.data_pools {
+                if interrupt::is_requested() {
+                    bar.finish_and_clear();
+                    return Err(AppError::Interrupted);
+                }
                 bar.set_message(format!("Генерую пул '{}'", pool_name));
-                let pool_data = analyzer.get_pool_data(&pool_config.gemini_prompt_for_pool).await?;
+                debug!("Заповнюю пул '{}': {}", pool_name, pool_config.description);
+                let prompt = &pool_config.gemini_prompt_for_pool;
+                let required = required_distinct_values(&architectural_plan, plan_tasks, pool_name, pool_config.uniqueness_ratio);
+                let local = if options.skip_pools {
+                    // Заглушки нічого не коштують, тож з запасом: по значенню на кожен рядок найбільшої таблиці
+                    let size = required_distinct_values(&architectural_plan, plan_tasks, pool_name, 1.0);
+                    Some(placeholder_pool(pool_name, LOCAL_POOL_SIZE.max(size)))
+                } else if prefer_local_pools {
+                    // Зі зерном запуску пул відтворюваний і не залежить від порядку заповнення пулів
+                    let mut pool_rng = match global_seed {
+                        Some(seed) => StdRng::seed_from_u64(seed ^ stable_hash(&format!("pool:{}", pool_name))),
+                        None => StdRng::from_entropy(),
+                    };
+                    lorem::local_pool(&mut pool_rng, pool_name, lang, LOCAL_POOL_SIZE.max(required))
+                } else {
+                    None
+                };
+                let is_local = local.is_some();
+                let cached = match &pool_cache {
+                    Some(cache) if !is_local && !options.refresh_pools => cache.load(prompt),
+                    _ => None,
+                };
+                let mut from_gemini = false;
+                let mut pool_data = match (local, cached) {
+                    (Some(values), _) => {
+                        debug!("Пул '{}' заповнено локально ({} значень)", pool_name, values.len());
+                        values
+                    }
+                    (None, Some(values)) => {
+                        debug!("Пул '{}' взято з кешу ({} значень)", pool_name, values.len());
+                        values
+                    }
+                    (None, None) => {
+                        from_gemini = true;
+                        analyzer.get_pool_data(prompt).await?
+                    }
+                };
+
+                // Для uniqueness_ratio пул має містити достатньо різних значень для найбільшої таблиці, що його використовує
+                if !is_local && distinct_count(&pool_data) < required {
+                    info!(
+                        "🔁 Пул '{}' містить {} унікальних значень, а потрібно щонайменше {}. Запитую додаткові...",
+                        pool_name, distinct_count(&pool_data), required
+                    );
+                    let extended_prompt = format!("{}\n\nProvide at least {} unique values.", prompt, required);
+                    match analyzer.get_pool_data(&extended_prompt).await {
+                        Ok(more) => {
+                            pool_data.extend(more);
+                            from_gemini = true;
+                        }
+                        // Вже отриманих значень вистачить, щоб продовжити, тож лише попереджаємо нижче
+                        Err(e) => warn!("Не вдалося доповнити пул '{}': {}", pool_name, e),
+                    }
+                }
+                let distinct = distinct_count(&pool_data);
+                if distinct < required {
+                    warn!(
+                        "⚠️  Пул '{}' містить лише {} унікальних значень, а для uniqueness_ratio {} потрібно {} (бракує {}). Значення повторюватимуться частіше",
+                        pool_name, distinct, pool_config.uniqueness_ratio, required, required - distinct
+                    );
+                }
+
+                if from_gemini {
+                    if let Some(cache) = &pool_cache {
+                        // Невдалий запис у кеш не повинен зупиняти заповнення
+                        if let Err(e) = cache.store(prompt, &pool_data) {
+                            warn!("Не вдалося зберегти пул '{}' у кеш: {}", pool_name, e);
+                        }
+                    }
+                }
                 let pool_values: Vec<Value> = pool_data.into_iter().map(|s| serde_json::from_str(&s).unwrap_or(Value::String(s))).collect();
                 data_pools.insert(pool_name.clone(), pool_values);
                 bar.inc(1);
             }
             bar.finish_with_message("✅ Пули даних заповнено!");
         }
+        self.load_query_values(&architectural_plan, &mut data_pools).await?;
+        summary.pools_elapsed = pools_started.elapsed();
 
-        let entity_generator = EntityGenerator::new();
+        let mut entity_generator = EntityGenerator::new(lang);
+        for (name, generator) in &self.generators {
+            entity_generator.register(name, Arc::clone(generator));
+        }
+        // Поки відкрите з'єднання запуску, решта запитів іде через нього, тож запуску достатньо одного з'єднання пулу
+        let populated_parents = self.populated_parents(plan_tasks).await?;
+        let transaction_mode = config.generation.as_ref().map(|g| g.transaction_mode).unwrap_or_default();
+        let mut connection = match transaction_mode {
+            TransactionMode::PerTable => RunConnection::PerTable(self.db_client.pool().acquire().await?),
+            TransactionMode::Global => {
+                info!("🔒 Усі таблиці заповнюються в одній транзакції");
+                // Дедлок чи конфлікт серіалізації перериває всю транзакцію запуску, а не лише таблицю:
+                // повторне заповнення таблиці в ній неможливе (див. `seed_table`)
+                retries.transient_retries = 0;
+                RunConnection::Global(self.db_client.pool().begin().await?)
+            }
+        };
         let mut generated_pks: DataPools = HashMap::new();
+        // `fk` на заповнену таблицю поза планом посилається на її наявні ключі
+        for parent in populated_parents {
+            let existing_pks = self.existing_pks(connection.as_conn(), &parent, existing_pks_limit).await?;
+            info!("🔗 Таблиці '{}' немає в плані: посилання на неї використовуватимуть {} наявних ключів", parent, existing_pks.len());
+            if !existing_pks.is_empty() {
+                generated_pks.insert(parent, existing_pks);
+            }
+        }
         
         let graph = self.build_plan_dependency_graph(plan_tasks);
-        let sorted_tables = toposort(&graph, None).map_err(|_| AppError::CyclicDependency)?;
+        let sorted_tables = toposort(&graph, None)
+            .map_err(|_| AppError::CyclicDependency(validator::describe_cycle(&self.schema, &graph).unwrap_or_default()))?;
+        let table_order = options
+            .table_order
+            .as_deref()
+            .or_else(|| config.generation.as_ref().map(|g| g.table_order.as_slice()))
+            .unwrap_or_default();
+        let sorted_tables = if table_order.is_empty() { sorted_tables } else { apply_table_order(&graph, sorted_tables, table_order)? };
 
-        println!("\n🚀 Порядок заповнення таблиць визначено:");
+        info!("🚀 Порядок заповнення таблиць визначено:");
         for (i, table_name) in sorted_tables.iter().enumerate() {
-            println!("   {}. {}", i + 1, style(table_name).cyan());
+            info!("   {}. {}", i + 1, style(table_name).cyan());
         }
 
+        let total_rows: u64 = sorted_tables
+            .iter()
+            .filter(|table| architectural_plan.entity_templates.iter().any(|e| &e.target_table == *table))
+            .filter_map(|table| plan_tasks.iter().find(|t| &t.table == table))
+            .map(|t| t.rows.count() as u64)
+            .sum();
+        let progress = RunProgress::new(total_rows)?;
+
         for table_name in sorted_tables {
             // Знаходимо і задачу, і шаблон сутності для поточної таблиці
-            if let Some(task) = plan_tasks.iter().find(|t| t.table == table_name) {
+            if let Some(planned_task) = plan_tasks.iter().find(|t| t.table == table_name) {
+                let task = &match configured_tasks.iter().find(|t| t.table == table_name).map(|t| &t.rows) {
+                    Some(relative @ Rows::PerParent { parent, .. }) => {
+                        // Пропущений через --seed-only-empty батько не має звіту, тож рахуємо від його наявних рядків
+                        let parent_rows = summary.tables.iter().find(|r| &r.table == parent).map(|r| r.inserted)
+                            .or_else(|| generated_pks.get(parent.as_str()).map(|pks| pks.len() as u64))
+                            .unwrap_or(0);
+                        let rows = jittered_rows(relative.resolve(parent_rows), planned_task.rows_jitter, table_rng_seed(global_seed, planned_task));
+                        let rows = progress.multi.suspend(|| capped_rows(table_name, rows, max_rows));
+                        // Загальний прогрес рахувався за оцінкою, тож виправляємо його на фактичну кількість
+                        let estimate = planned_task.rows.count() as u64;
+                        progress.total.set_length(progress.total.length().unwrap_or(0).saturating_sub(estimate) + rows as u64);
+                        SeedingTask { rows: Rows::Fixed(rows), ..planned_task.clone() }
+                    }
+                    _ => planned_task.clone(),
+                };
+                if options.seed_only_empty && self.db_client.table_has_rows(connection.as_conn(), table_name).await? {
+                    let existing_pks = self.existing_pks(connection.as_conn(), table_name, existing_pks_limit).await?;
+                    progress.multi.suspend(|| {
+                        info!("⏭️  Пропускаю таблицю '{}': вона вже містить дані (--seed-only-empty, наявних ключів: {})", table_name, existing_pks.len());
+                    });
+                    progress.total.inc(task.rows.count() as u64);
+                    if !existing_pks.is_empty() {
+                        generated_pks.insert(table_name.to_string(), existing_pks);
+                    }
+                    continue;
+                }
                 if let Some(entity_template) = architectural_plan.entity_templates.iter().find(|e| e.target_table == table_name) {
-                    println!("\n🌱 Заповнюю таблицю '{}' ({} рядків) сутностями '{}'...", style(table_name).bold(), task.rows, style(&entity_template.entity_name).cyan());
-                    let pks = self.seed_table(task, entity_template, &entity_generator, &data_pools, &generated_pks).await?;
+                    // Без рядків батьківської таблиці `fk` не матиме на що посилатися
+                    let failed_parent = graph
+                        .neighbors_directed(table_name, petgraph::Direction::Incoming)
+                        .find(|parent| summary.failed.iter().any(|f| f.table == *parent));
+                    if let Some(parent) = failed_parent {
+                        progress.multi.suspend(|| {
+                            warn!("⏭️  Пропускаю таблицю '{}': батьківську таблицю '{}' не заповнено", table_name, parent);
+                        });
+                        progress.total.inc(task.rows.count() as u64);
+                        summary.failed.push(TableFailure {
+                            table: table_name.to_string(),
+                            reason: format!("пропущено, бо не заповнено батьківську таблицю '{}'", parent),
+                        });
+                        continue;
+                    }
+
+                    progress.multi.suspend(|| {
+                        info!("🌱 Заповнюю таблицю '{}' ({} рядків) сутностями '{}'...", style(table_name).bold(), task.rows, style(&entity_template.entity_name).cyan());
+                    });
+                    let rng_seed = table_rng_seed(global_seed, task);
+                    let result = self
+                        .seed_table(connection.as_conn(), task, entity_template, &entity_generator, &data_pools, &generated_pks, &retries, redacted_columns, rng_seed, append, &progress)
+                        .await;
+                    let (pks, report) = match result {
+                        Ok(seeded) => seeded,
+                        Err(e) if options.continue_on_error && !matches!(e, AppError::Interrupted) => {
+                            progress.multi.suspend(|| {
+                                error!("{}", style(format!("❌ Таблицю '{}' не заповнено: {}", table_name, e)).red());
+                            });
+                            summary.failed.push(TableFailure { table: table_name.to_string(), reason: e.to_string() });
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    if report.count_mismatch(mismatch_threshold) {
+                        progress.multi.suspend(|| {
+                            warn!(
+                                "⚠️  Таблиця '{}': запитано {} рядків, а фактично додано {}",
+                                table_name, report.attempted, report.verified.unwrap_or(report.inserted)
+                            );
+                        });
+                    }
+                    summary.tables.push(report);
                     if !pks.is_empty() {
                         generated_pks.insert(table_name.to_string(), pks);
                     }
                 } else {
-                    println!("{}", style(format!("⚠️  Пропускаю таблицю '{}', оскільки для неї не знайдено шаблон сутності в плані Gemini.", table_name)).yellow());
+                    warn!("{}", style(format!("⚠️  Пропускаю таблицю '{}', оскільки для неї не знайдено шаблон сутності в плані Gemini.", table_name)).yellow());
                 }
             }
         }
         
-        println!("\n✨ Заповнення бази даних успішно завершено!");
+        progress.total.finish();
+
+        match connection {
+            RunConnection::Global(tx) => tx.commit().await?,
+            // Повертаємо з'єднання в пул: ANALYZE нижче йде через пул
+            RunConnection::PerTable(conn) => drop(conn),
+        }
+
+        // Статистику оновлюємо лише після коміту, інакше ANALYZE не побачить нових рядків
+        let vacuum = config.generation.as_ref().is_some_and(|g| g.vacuum_analyze);
+        if vacuum || config.generation.as_ref().is_some_and(|g| g.analyze_after) {
+            for report in &summary.tables {
+                match self.db_client.analyze(&report.table, vacuum).await {
+                    Ok(()) => summary.analyzed.push(report.table.clone()),
+                    // Застаріла статистика не псує вже вставлені дані, тож лише попереджаємо
+                    Err(e) => warn!("⚠️  Не вдалося оновити статистику таблиці '{}': {}", report.table, e),
+                }
+            }
+            summary.vacuumed = vacuum;
+        }
+        if summary.failed.is_empty() {
+            info!("✨ Заповнення бази даних успішно завершено!");
+        } else {
+            warn!("⚠️  Заповнення завершено, але {} таблиць не заповнено.", summary.failed.len());
+        }
+        summary.elapsed = started.elapsed();
+        summary.tokens = analyzer.token_usage();
+        summary.api_timings = analyzer.api_timings();
+        summary.api_call_budget = config.gemini.as_ref().and_then(|g| g.max_api_calls);
+        if let Some(gemini) = &config.gemini {
+            if gemini.input_price_per_million.is_some() || gemini.output_price_per_million.is_some() {
+                summary.estimated_cost = Some(summary.tokens.estimated_cost(
+                    gemini.input_price_per_million.unwrap_or(0.0),
+                    gemini.output_price_per_million.unwrap_or(0.0),
+                ));
+            }
+        }
+        summary.plans.push(architectural_plan);
+        Ok(summary)
+    }
+
+    /// Наявні значення PK таблиці (не більше `limit`) у тому ж вигляді, що й ключі, згенеровані під час запуску
+    /// (порожньо для таблиці без PK)
+    async fn existing_pks(&self, conn: &mut PgConnection, table_name: &str, limit: u32) -> AppResult<Vec<Value>> {
+        let Some(table) = self.schema.tables.get(table_name) else { return Ok(Vec::new()) };
+        let Some(pk_column) = table.primary_key_column.as_deref().and_then(|pk| table.columns.iter().find(|c| c.name == pk)) else {
+            return Ok(Vec::new());
+        };
+        self.db_client.fetch_primary_keys(conn, table_name, &pk_column.name, pk_return_type(pk_column), limit).await
+    }
+
+    /// Один раз виконує запити генераторів `from_query` і зберігає результати в `data_pools`
+    /// (під ключем `from_query_key`), щоб під час генерації рядків не звертатися до БД
+    async fn load_query_values(&self, plan: &ArchitecturalPlan, data_pools: &mut DataPools) -> AppResult<()> {
+        let fields = plan.entity_templates.iter().flat_map(|t| t.fields.iter()).filter(|f| f.generator == "from_query");
+        for field in fields {
+            let Some(key) = from_query_key(&field.params) else { continue };
+            if data_pools.contains_key(&key) {
+                continue;
+            }
+            let query = match field.params.get("query").and_then(|v| v.as_str()) {
+                Some(query) => query.to_string(),
+                None => {
+                    let table = field.params.get("table").and_then(|v| v.as_str()).unwrap_or_default();
+                    let column = field.params.get("column").and_then(|v| v.as_str()).unwrap_or_default();
+                    format!("SELECT DISTINCT \"{}\" FROM {} WHERE \"{}\" IS NOT NULL", column, self.db_client.qualified(table), column)
+                }
+            };
+            let values = self.db_client.fetch_values(&query).await
+                .map_err(|e| AppError::Custom(format!("Не вдалося виконати запит `from_query` для '{}': {}", field.column_name, e)))?;
+            if values.is_empty() {
+                warn!("⚠️  Запит `from_query` для '{}' не повернув жодного значення, колонка буде NULL", field.column_name);
+            }
+            debug!("`from_query` для '{}': {} значень", field.column_name, values.len());
+            data_pools.insert(key, values);
+        }
         Ok(())
     }
 
+    /// Прибирає з шаблонів колонки, які заповнить сама БД. `GENERATED ALWAYS` колонки не приймають явних
+    /// значень, тож прибираються завжди; решта колонок з DEFAULT, IDENTITY BY DEFAULT — лише з `prefer_db_defaults`.
+    /// Колонки з `column_overrides` та ті, від яких залежать інші поля рядка, залишаються
+    /// (для `GENERATED ALWAYS` — лише останні, і їхні значення не потрапляють в INSERT, див. `seed_table`).
+    fn drop_fields_with_db_defaults(&self, plan: &mut ArchitecturalPlan, plan_tasks: &[SeedingTask], prefer_db_defaults: bool) {
+        for template in &mut plan.entity_templates {
+            let Some(table_schema) = self.schema.tables.get(&template.target_table) else { continue };
+            let overrides = plan_tasks
+                .iter()
+                .find(|t| t.table == template.target_table)
+                .map(|t| &t.column_overrides);
+            let referenced = referenced_fields(template);
+
+            template.fields.retain(|field| {
+                let Some(column) = table_schema.columns.iter().find(|c| c.name == field.column_name) else { return true };
+                let keep = if column.db_generated_always {
+                    referenced.contains(&field.column_name)
+                } else {
+                    !prefer_db_defaults
+                        || !column.has_db_default()
+                        || overrides.is_some_and(|o| o.contains_key(&field.column_name))
+                        || referenced.contains(&field.column_name)
+                };
+                if !keep {
+                    debug!("Колонка '{}.{}' отримає значення за замовчуванням з БД", template.target_table, field.column_name);
+                }
+                keep
+            });
+        }
+    }
+
+    /// Узгоджує параметри генераторів із фактичними типами колонок
+    fn adapt_templates_to_schema(&self, plan: &mut ArchitecturalPlan) {
+        for template in &mut plan.entity_templates {
+            let Some(table_schema) = self.schema.tables.get(&template.target_table) else { continue };
+
+            for field in &mut template.fields {
+                let Some(column) = table_schema.columns.iter().find(|c| c.name == field.column_name) else { continue };
+
+                // Для `date` колонок генеруємо чисту дату замість повної мітки часу
+                if field.generator == "datetime_range" && column.data_type == "date" {
+                    field.params.entry("granularity".to_string()).or_insert_with(|| json!("date"));
+                }
+
+                // Для enum колонок та CHECK `IN (...)` беремо значення прямо зі схеми, а не покладаємося на Gemini
+                let allowed_values = if column.is_enum() {
+                    Some(column.enum_labels.clone())
+                } else {
+                    table_schema.check_allowed_values(&column.name)
+                };
+                if let Some(allowed_values) = allowed_values {
+                    let labels: Vec<Value> = allowed_values.iter().map(|l| json!(l)).collect();
+                    let options_are_valid = field.generator == "choice"
+                        && field.params.get("options").and_then(|v| v.as_array()).is_some_and(|opts| {
+                            !opts.is_empty() && opts.iter().all(|o| labels.contains(o))
+                        });
+                    if !options_are_valid {
+                        // Ваги з column_overrides прив'язані до міток, тож переживають заміну `options`
+                        let weights = field.params.remove("weights").filter(|w| w.is_object());
+                        field.generator = "choice".to_string();
+                        field.params = HashMap::from([("options".to_string(), Value::Array(labels))]);
+                        if let Some(weights) = weights {
+                            field.params.insert("weights".to_string(), weights);
+                        }
+                    }
+                    if let Some(weights) = field.params.get("weights").and_then(|w| w.as_object()) {
+                        for label in weights.keys().filter(|l| !allowed_values.contains(l)) {
+                            warn!("⚠️  Вага для '{}' у '{}.{}' проігнорована: такого значення немає у схемі", label, template.target_table, column.name);
+                        }
+                    }
+                }
+
+                // PostGIS приймає точку лише як WKT (текст приводиться до типу колонки в `pg_cast_for`)
+                if field.generator == "geo_point" && matches!(column.udt_name.as_str(), "geography" | "geometry") {
+                    field.params.insert("as".to_string(), json!("wkt"));
+                }
+
+                if field.generator == "number_range" {
+                    let check_bounds = table_schema.check_bounds(&column.name);
+                    normalize_number_range(&template.target_table, column, check_bounds, &mut field.params);
+                    // Суми в `money` з копійками, а не лише цілі
+                    if column.data_type == "money" {
+                        field.params.entry("decimals".to_string()).or_insert_with(|| json!(2));
+                    }
+                }
+
+                // Текст для `varchar(n)` генеруємо одразу потрібної довжини, щоб не обрізати його посеред слова
+                if let (Some(column_max), "words" | "sentence" | "paragraphs") = (column.max_length, field.generator.as_str()) {
+                    let max_length = field.params.get("max_length").and_then(|v| v.as_i64()).map_or(column_max as i64, |m| m.min(column_max as i64));
+                    field.params.insert("max_length".to_string(), json!(max_length));
+                }
+
+                // Ключ з префіксом має вміститися у `varchar(n)` цілком
+                if let (Some(column_max), "pk_hash") = (column.max_length, field.generator.as_str()) {
+                    let prefix_len = field.params.get("prefix").and_then(|v| v.as_str()).map_or(0, |p| p.chars().count()) as i64;
+                    let length = field.params.get("length").and_then(|v| v.as_i64()).unwrap_or(20);
+                    let fitted = length.min(column_max as i64 - prefix_len).max(0);
+                    if fitted != length {
+                        field.params.insert("length".to_string(), json!(fitted));
+                    }
+                }
+
+                // NULL у NOT NULL колонку вставити неможливо
+                if field.generator == "fk" && !column.is_nullable && field.params.remove("null_chance").is_some() {
+                    warn!("⚠️  Ігнорую `null_chance` для '{}.{}': колонка NOT NULL", template.target_table, column.name);
+                }
+            }
+        }
+    }
+
+    /// Заповнює таблицю, повторюючи її заповнення з нуля після дедлоку (`40P01`) чи конфлікту
+    /// серіалізації (`40001`). Інші помилки повертаються одразу. У режимі `global` повторів немає
+    /// (`transient_retries` = 0): Postgres скасовує всю транзакцію запуску разом з уже заповненими таблицями.
+    #[allow(clippy::too_many_arguments)]
     async fn seed_table(
         &self,
+        conn: &mut PgConnection,
+        task: &SeedingTask,
+        template: &crate::gemini_analyzer::EntityTemplate,
+        generator: &EntityGenerator,
+        pools: &DataPools,
+        all_previous_pks: &DataPools,
+        retries: &RetryPolicy,
+        redacted_columns: &[String],
+        rng_seed: Option<u64>,
+        append: bool,
+        progress: &RunProgress,
+    ) -> AppResult<(Vec<Value>, TableReport)> {
+        let mut attempt = 0;
+        let total_position = progress.total.position();
+        loop {
+            let result = self
+                .seed_table_once(&mut *conn, task, template, generator, pools, all_previous_pks, retries.row_retries, redacted_columns, rng_seed, append, progress)
+                .await;
+            let transient = match &result {
+                Err(e) if attempt < retries.transient_retries => transient_db_error(e),
+                _ => None,
+            };
+            let Some(db_err) = transient else {
+                return result;
+            };
+
+            attempt += 1;
+            progress.total.set_position(total_position);
+            let backoff = Duration::from_millis(500 * attempt as u64);
+            warn!(
+                "⚠️  Таблиця '{}': {} ({}), повторюю заповнення через {} мс (спроба {} з {})",
+                template.target_table,
+                db_err.message(),
+                db_err.code().unwrap_or_default(),
+                backoff.as_millis(),
+                attempt,
+                retries.transient_retries
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn seed_table_once(
+        &self,
+        conn: &mut PgConnection,
         task: &SeedingTask,
         template: &crate::gemini_analyzer::EntityTemplate,
         generator: &EntityGenerator,
         pools: &DataPools,
         all_previous_pks: &DataPools,
-    ) -> AppResult<Vec<Value>> {
-        let bar = ProgressBar::new(task.rows as u64);
+        max_row_retries: u32,
+        redacted_columns: &[String],
+        rng_seed: Option<u64>,
+        append: bool,
+        progress: &RunProgress,
+    ) -> AppResult<(Vec<Value>, TableReport)> {
+        let started = Instant::now();
+        let mut generation = Duration::ZERO;
+        let mut insertion = Duration::ZERO;
+        let mut inserted: u64 = 0;
+        let count_before = self.db_client.count_rows(&mut *conn, &template.target_table).await?;
+        let rows = task.rows.count();
+        let bar = progress.table_bar(&template.target_table, rows as u64)?;
         let mut generated_pks_for_this_table = Vec::new();
         
         let table_schema = self.schema.tables.get(&template.target_table)
             .ok_or_else(|| AppError::Custom(format!("Схема для таблиці '{}' не знайдена", template.target_table)))?;
 
+        // Фактична кількість батьківських ключів може бути меншою за заплановану (ON CONFLICT, наявні дані)
+        let shortages = unique_fk_shortages(template, rows, |parent| all_previous_pks.get(parent).map(Vec::len));
+        if !shortages.is_empty() {
+            return Err(AppError::Custom(shortages.join("; ")));
+        }
+
         let pk_col_name = table_schema.primary_key_column.as_deref();
+        let pk_return_type = match pk_col_name {
+            Some(pk_name) => {
+                let pk_col_schema = table_schema.columns.iter().find(|c| c.name == pk_name).ok_or_else(|| {
+                    AppError::Custom(format!("Не знайдено схему для PK колонки '{}' таблиці '{}'", pk_name, table_schema.name))
+                })?;
+                pk_return_type(pk_col_schema)
+            }
+            None => "text",
+        };
+
+        // Nullable колонки, що посилаються на PK цієї ж таблиці (наприклад, categories.parent_id)
+        let self_ref_columns: Vec<&str> = match pk_col_name {
+            Some(pk_name) => self.schema.foreign_keys.iter()
+                .filter(|fk| fk.from_table == table_schema.name && fk.to_table == table_schema.name && fk.to_column == pk_name)
+                .filter(|fk| table_schema.columns.iter().any(|c| c.name == fk.from_column && c.is_nullable))
+                .map(|fk| fk.from_column.as_str())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // GENERATED ALWAYS колонки заповнює лише БД; поле могло залишитися в шаблоні через column_overrides
+        // або посилання з інших полів рядка
+        let generated_always: Vec<&str> =
+            table_schema.columns.iter().filter(|c| c.db_generated_always).map(|c| c.name.as_str()).collect();
+
+        // Поля, що посилаються на інші поля рядка, мають генеруватися після них
+        let fields = order_fields(template)?;
+        let mut table_state = TableState::default();
+        // Окремий генератор для кожної таблиці: зі зерном таблиця заповнюється однаково незалежно від інших
+        let mut rng = match rng_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        // У режиму `global` це SAVEPOINT всередині спільної транзакції запуску
+        let mut tx = Connection::begin(&mut *conn).await?;
+
+        // Дозаповнення: `sequence` для PK продовжує після вже наявних рядків
+        if let Some(pk_name) = pk_col_name.filter(|_| append && pk_return_type == "bigint") {
+            if fields.iter().any(|f| f.column_name == pk_name && f.generator == "sequence") {
+                if let Some(max_pk) = self.db_client.max_integer(&mut tx, &template.target_table, pk_name).await? {
+                    info!("➕ Таблиця '{}': продовжую {} після {}", template.target_table, pk_name, max_pk);
+                    table_state.sequence_starts.insert(pk_name.to_string(), max_pk.saturating_add(1));
+                }
+            }
+        }
+        for row_index in 0..rows {
+            table_state.row_index = row_index as u64;
+            if interrupt::is_requested() {
+                bar.finish_and_clear();
+                tx.rollback().await?;
+                warn!("↩️  Транзакцію для таблиці '{}' відкочено", template.target_table);
+                return Err(AppError::Interrupted);
+            }
 
-        let mut tx = self.db_client.pool().begin().await?;
-        for _ in 0..task.rows {
             let mut available_pks = all_previous_pks.clone();
             available_pks.insert(template.target_table.clone(), generated_pks_for_this_table.clone());
-            let entity = generator.generate_entity(&template.fields, pools, &available_pks)?;
-
-            let columns: Vec<String> = entity.keys().cloned().collect();
-            let values: Vec<Value> = columns.iter().map(|k| entity.get(k).unwrap().clone()).collect();
-            let column_names = columns.iter().map(|s| format!("\"{}\"", s)).collect::<Vec<_>>().join(", ");
-            
-            let placeholders: String = columns.iter().enumerate().map(|(i, col_name)| {
-                let placeholder_index = i + 1;
-                let col_schema = table_schema.columns.iter().find(|c| &c.name == col_name);
-                if let Some(schema) = col_schema {
-                    match schema.data_type.as_str() {
-                        "timestamp with time zone" | "timestamp without time zone" => format!("${}::timestamp", placeholder_index),
-                        "date" => format!("${}::date", placeholder_index),
-                        "uuid" => format!("${}::uuid", placeholder_index),
-                        _ => format!("${}", placeholder_index),
-                    }
-                } else {
-                    format!("${}", placeholder_index)
+
+            // Кожна спроба вставки йде в окремому SAVEPOINT: порушення обмеження відкочує лише цей рядок,
+            // і ми генеруємо його заново замість того, щоб втратити всю транзакцію таблиці
+            let mut attempt = 0;
+            let (affected, returned_row) = loop {
+                let generation_started = Instant::now();
+                let mut entity = generator.generate_entity(&fields, pools, &available_pks, &mut table_state, &mut rng)?;
+                generation += generation_started.elapsed();
+
+                // Самопосилання заповнюємо окремим проходом після вставки всіх рядків
+                entity.retain(|column, _| !self_ref_columns.contains(&column.as_str()) && !generated_always.contains(&column.as_str()));
+
+                let columns: Vec<String> = entity.keys().cloned().collect();
+                let values: Vec<Value> = columns.iter().map(|k| entity.get(k).unwrap().clone()).collect();
+                let column_names = columns.iter().map(|s| format!("\"{}\"", s)).collect::<Vec<_>>().join(", ");
+
+                let placeholders: String = columns.iter().enumerate().map(|(i, col_name)| {
+                    let col_schema = table_schema.columns.iter().find(|c| &c.name == col_name);
+                    placeholder_for(i + 1, col_schema)
+                }).collect::<Vec<_>>().join(", ");
+
+                let insert_into = format!("INSERT INTO {} ({})", self.db_client.qualified(&template.target_table), column_names);
+                let mut sql = format!("{} VALUES ({})", insert_into, placeholders);
+
+                if let Some(pk_name) = pk_col_name {
+                    sql.push_str(&format!(" RETURNING \"{}\"::{}", pk_name, pk_return_type));
                 }
-            }).collect::<Vec<_>>().join(", ");
-            
-            let mut sql = format!("INSERT INTO \"{}\" ({}) VALUES ({})", template.target_table, column_names, placeholders);
-
-            if let Some(pk_name) = pk_col_name {
-                sql.push_str(&format!(" RETURNING \"{}\"", pk_name));
-            }
-            
-            let mut query = sqlx::query(&sql);
-            for (i, col_name) in columns.iter().enumerate() {
-                let val = &values[i];
-                let col_schema = table_schema.columns.iter().find(|c| &c.name == col_name);
-
-                if let Some(schema) = col_schema {
-                    match schema.data_type.as_str() {
-                        "integer" | "bigint" | "smallint" | "int4" => {
-                            // Примусово перетворюємо в число
-                            let int_val = if let Some(i) = val.as_i64() {
-                                i
-                            } else if let Some(b) = val.as_bool() {
-                                if b { 1 } else { 0 }
-                            } else if let Some(s) = val.as_str() {
-                                s.parse::<i64>().unwrap_or(0)
-                            } else {
-                                0 // Дефолтне значення
-                            };
-                            query = query.bind(int_val);
-                        }
-                        "boolean" => {
-                            // Якщо в БД тип boolean
-                            let bool_val = if let Some(b) = val.as_bool() {
-                                b
-                            } else if let Some(i) = val.as_i64() {
-                                i != 0
-                            } else if let Some(s) = val.as_str() {
-                                s == "true" || s == "1"
-                            } else {
-                                false
-                            };
-                            query = query.bind(bool_val);
+
+                let mut query = sqlx::query(&sql);
+                for (i, col_name) in columns.iter().enumerate() {
+                    let col_schema = table_schema.columns.iter().find(|c| &c.name == col_name);
+                    query = bind_value(query, &values[i], col_schema)?;
+                }
+
+                let insertion_started = Instant::now();
+                let mut savepoint = Connection::begin(&mut *tx).await?;
+                let result = if pk_col_name.is_some() {
+                    // Рядок може не повернутися, якщо вставку було пропущено (ON CONFLICT, тригер)
+                    query.fetch_optional(&mut *savepoint).await.map(|row| (row.is_some() as u64, row))
+                } else {
+                    query.execute(&mut *savepoint).await.map(|res| (res.rows_affected(), None))
+                };
+                insertion += insertion_started.elapsed();
+
+                match result {
+                    Ok(outcome) => {
+                        savepoint.commit().await?;
+                        break outcome;
+                    }
+                    Err(sqlx::Error::Database(db_err)) if db_err.code().is_some_and(|code| code.starts_with("23")) => {
+                        savepoint.rollback().await?;
+                        let constraint = db_err.constraint().unwrap_or("невідоме").to_string();
+                        if attempt >= max_row_retries {
+                            bar.finish_and_clear();
+                            return Err(AppError::ConstraintViolation {
+                                table: template.target_table.clone(),
+                                constraint,
+                                attempts: attempt + 1,
+                                message: db_err.message().to_string(),
+                                values: serde_json::to_string(&redact_entity(&template.target_table, &entity, redacted_columns))?,
+                            });
                         }
-                        "numeric" | "decimal" | "real" | "double precision" => {
-                            let float_val = if let Some(f) = val.as_f64() {
-                                f
-                            } else if let Some(s) = val.as_str() {
-                                s.parse::<f64>().unwrap_or(0.0)
+                        attempt += 1;
+                        debug!("Рядок для '{}' порушив обмеження '{}', генерую заново (спроба {})", template.target_table, constraint, attempt + 1);
+                    }
+                    Err(e) => {
+                        bar.finish_and_clear();
+                        let literals = columns.iter().enumerate().map(|(i, col_name)| {
+                            let col_schema = table_schema.columns.iter().find(|c| &c.name == col_name);
+                            let literal = if is_redacted(&template.target_table, col_name, redacted_columns) {
+                                "'***'".to_string()
                             } else {
-                                0.0
+                                sql_literal(&values[i])
                             };
-                            query = query.bind(float_val);
-                        }
-                        "character varying" | "text" | "varchar" | "uuid" | "timestamp with time zone" | "timestamp without time zone" | "date" => {
-                            // Для цих типів ми покладаємося на кастинг в SQL (::timestamp, ::uuid)
-                            // і просто передаємо рядок
-                            query = query.bind(val.as_str().unwrap_or("").to_string());
-                        }
-                        _ => {
-                            // Для всіх інших (json, numeric, etc.)
-                            query = query.bind(val);
-                        }
+                            placeholder_for(i + 1, col_schema).replacen(&format!("${}", i + 1), &literal, 1)
+                        });
+                        let rendered = format!("{} VALUES ({})", insert_into, literals.collect::<Vec<_>>().join(", "));
+                        return Err(AppError::InsertFailed { sql: rendered, source: e });
                     }
-                } else {
-                    // Якщо схему не знайдено, біндимо як є
-                    query = query.bind(val);
                 }
-            }
-            
-            if let Some(pk_name) = pk_col_name {
-                let row = query.fetch_one(&mut *tx).await?;
-
-                let pk_col_schema = table_schema.columns.iter().find(|c| &c.name == pk_name)
-                    .ok_or_else(|| AppError::Custom(format!("Не знайдено схему для PK колонки {}", pk_name)))?;
-
-                let pk_val: Value = match pk_col_schema.data_type.as_str() {
-                    "character varying" | "text" | "varchar" | "uuid" => {
-                        let val: String = row.get(0);
-                        Value::String(val)
-                    },
-                    "integer" | "smallint" => {
-                        let val: i32 = row.get(0);
-                        json!(val)
-                    },
-                    "bigint" => {
-                        let val: i64 = row.get(0);
-                        json!(val)
-                    }
-                    _ => return Err(AppError::Custom(format!("Непідтримуваний тип даних для первинного ключа: {}", pk_col_schema.data_type)))
+            };
+            inserted += affected;
+
+            // Рядка немає, якщо в таблиці немає PK або вставку було пропущено
+            if let Some(row) = returned_row {
+                let pk_val: Value = match pk_return_type {
+                    "bigint" => json!(row.try_get::<i64, _>(0)?),
+                    "float8" => json!(row.try_get::<f64, _>(0)?),
+                    _ => Value::String(row.try_get::<String, _>(0)?),
                 };
-                
+
                 generated_pks_for_this_table.push(pk_val);
-            } else {
-                query.execute(&mut *tx).await?;
             }
             bar.inc(1);
+            progress.total.inc(1);
+        }
+
+        if let (Some(pk_name), false) = (pk_col_name, self_ref_columns.is_empty()) {
+            let chance = task.self_reference_chance.unwrap_or(0.5).clamp(0.0, 1.0);
+            let insertion_started = Instant::now();
+            self.link_self_references(&mut tx, table_schema, pk_name, &self_ref_columns, &generated_pks_for_this_table, chance, &mut rng).await?;
+            insertion += insertion_started.elapsed();
         }
         tx.commit().await?;
 
-        bar.finish_with_message("Завершено");
-        Ok(generated_pks_for_this_table)
+        bar.finish_and_clear();
+        let count_after = self.db_client.count_rows(&mut *conn, &template.target_table).await?;
+        let report = TableReport {
+            table: template.target_table.clone(),
+            attempted: rows as u64,
+            inserted,
+            verified: Some(count_after.saturating_sub(count_before).max(0) as u64),
+            elapsed: started.elapsed(),
+            generation,
+            insertion,
+        };
+        Ok((generated_pks_for_this_table, report))
+    }
+
+    /// Другий прохід для самопосилальних FK: частина рядків отримує посилання на раніше вставлений рядок
+    /// цієї ж таблиці. Посилаємося лише на "старші" рядки, тож ієрархія завжди залишається деревом.
+    #[allow(clippy::too_many_arguments)]
+    async fn link_self_references(
+        &self,
+        conn: &mut PgConnection,
+        table_schema: &TableSchema,
+        pk_name: &str,
+        self_ref_columns: &[&str],
+        pks: &[Value],
+        chance: f64,
+        rng: &mut StdRng,
+    ) -> AppResult<()> {
+        let pk_schema = table_schema.columns.iter().find(|c| c.name == pk_name);
+
+        for &column in self_ref_columns {
+            let col_schema = table_schema.columns.iter().find(|c| c.name == column);
+            let sql = format!(
+                "UPDATE {} SET \"{}\" = {} WHERE \"{}\" = {}",
+                self.db_client.qualified(&table_schema.name), column, placeholder_for(1, col_schema), pk_name, placeholder_for(2, pk_schema)
+            );
+
+            let mut links: Vec<(usize, usize)> = Vec::new();
+            for child in 1..pks.len() {
+                if rng.gen_bool(chance) {
+                    links.push((rng.gen_range(0..child), child));
+                }
+            }
+            debug!("Таблиця '{}': зв'язую {} рядків через '{}'", table_schema.name, links.len(), column);
+
+            for (parent, child) in links {
+                let query = bind_value(sqlx::query(&sql), &pks[parent], col_schema)?;
+                bind_value(query, &pks[child], pk_schema)?.execute(&mut *conn).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Створює клієнт Gemini з параметрами з конфігурації. Ключ перевіряється лише під час першого запиту,
+/// тож із збереженим планом і закешованими пулами він не потрібен.
+pub fn create_analyzer(config: &crate::config::AppConfig) -> AppResult<GeminiAnalyzer> {
+    // Змінна середовища має пріоритет над ключем з конфігурації
+    let key_env = config.gemini.as_ref().and_then(|g| g.api_key_env.as_deref()).unwrap_or("GEMINI_API_KEY");
+    let gemini_key = std::env::var(key_env)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .or_else(|| config.gemini.as_ref().and_then(|g| g.api_key.clone()))
+        .unwrap_or_default();
+    let model = config.gemini.as_ref().map_or("gemini-1.5-flash-latest".to_string(), |g| g.model.clone());
+    let temperature = config.gemini.as_ref().and_then(|g| g.temperature).unwrap_or(0.7);
+    GeminiAnalyzer::new(gemini_key, model, temperature)
+        .with_output_limits(
+            config.gemini.as_ref().and_then(|g| g.top_p),
+            config.gemini.as_ref().and_then(|g| g.max_output_tokens),
+        )
+        .with_max_api_calls(config.gemini.as_ref().and_then(|g| g.max_api_calls))
+        .with_base_url(config.gemini.as_ref().and_then(|g| g.base_url.as_deref()))
+        .with_proxy(
+            config.gemini.as_ref().and_then(|g| g.proxy_url.as_deref()),
+            config.gemini.as_ref().is_some_and(|g| g.danger_accept_invalid_certs),
+        )
+}
+
+/// Помилка дедлоку (`40P01`) чи конфлікту серіалізації (`40001`), після якої таблицю варто заповнити заново
+fn transient_db_error(error: &AppError) -> Option<&dyn sqlx::error::DatabaseError> {
+    let db_err = match error {
+        AppError::Db(sqlx::Error::Database(db_err)) => db_err,
+        AppError::InsertFailed { source: sqlx::Error::Database(db_err), .. } => db_err,
+        _ => return None,
+    };
+    matches!(db_err.code().as_deref(), Some("40P01" | "40001")).then_some(db_err.as_ref())
+}
+
+/// Чи приховувати значення колонки в повідомленнях: запис у `redact_columns` — це `column` або `table.column`
+fn is_redacted(table: &str, column: &str, redacted_columns: &[String]) -> bool {
+    redacted_columns.iter().any(|entry| match entry.split_once('.') {
+        Some((t, c)) => t.eq_ignore_ascii_case(table) && c.eq_ignore_ascii_case(column),
+        None => entry.eq_ignore_ascii_case(column),
+    })
+}
+
+/// Копія рядка для повідомлень, у якій значення чутливих колонок замінено на "***"
+fn redact_entity(table: &str, entity: &HashMap<String, Value>, redacted_columns: &[String]) -> HashMap<String, Value> {
+    entity
+        .iter()
+        .map(|(column, value)| {
+            let shown = if is_redacted(table, column, redacted_columns) { json!("***") } else { value.clone() };
+            (column.clone(), shown)
+        })
+        .collect()
+}
+
+/// SQL-літерал для показу значення в тексті запиту (лише для повідомлень, не для виконання)
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
+
+/// Зерно для генератора таблиці: `generation.seed XOR seed` таблиці. Якщо задано лише глобальне зерно,
+/// його комбінуємо з хешем імені таблиці, щоб таблиці не отримували однакових послідовностей.
+fn table_rng_seed(global_seed: Option<u64>, task: &SeedingTask) -> Option<u64> {
+    match (global_seed, task.seed) {
+        (global, Some(table_seed)) => Some(global.unwrap_or(0) ^ table_seed),
+        (Some(global), None) => Some(global ^ stable_hash(&task.table)),
+        (None, None) => None,
+    }
+}
+
+/// Порядок заповнення з `table_order`: спершу вказані таблиці в заданому порядку, решта — після них
+/// в автоматичному порядку. Помилка, якщо таблиця опиняється перед батьківською, від якої залежить.
+fn apply_table_order<'a>(graph: &DiGraphMap<&'a str, ()>, auto_order: Vec<&'a str>, table_order: &[String]) -> AppResult<Vec<&'a str>> {
+    let mut order: Vec<&'a str> = Vec::with_capacity(auto_order.len());
+    for table in table_order {
+        match auto_order.iter().find(|t| **t == table.as_str()) {
+            Some(&t) if !order.contains(&t) => order.push(t),
+            Some(_) => {}
+            // У запуску з кількома БД тут опиняються й таблиці інших баз даних
+            None => warn!("⚠️  Таблиці '{}' з table_order немає серед таблиць цього запуску, пропускаю", table),
+        }
+    }
+    let rest: Vec<&'a str> = auto_order.into_iter().filter(|t| !order.contains(t)).collect();
+    order.extend(rest);
+
+    let position: HashMap<&str, usize> = order.iter().enumerate().map(|(i, t)| (*t, i)).collect();
+    for (parent, child, _) in graph.all_edges() {
+        if position[parent] > position[child] {
+            return Err(AppError::Custom(format!(
+                "table_order: таблиця '{}' стоїть перед '{}', від якої залежить. Перемістіть '{}' раніше або додайте її до table_order",
+                child, parent, parent
+            )));
+        }
+    }
+    Ok(order)
+}
+
+/// Поля `fk` з `unique: true`, для яких у батьківській таблиці менше ключів (`parent_keys`), ніж `rows`.
+/// Поля з `null_chance` та самопосилання не перевіряються: їм потрібно менше батьків, ніж рядків
+fn unique_fk_shortages(template: &EntityTemplate, rows: u32, parent_keys: impl Fn(&str) -> Option<usize>) -> Vec<String> {
+    template
+        .fields
+        .iter()
+        .filter(|f| f.generator == "fk" && f.params.get("unique").and_then(|v| v.as_bool()).unwrap_or(false))
+        .filter(|f| f.params.get("null_chance").and_then(|v| v.as_f64()).unwrap_or(0.0) <= 0.0)
+        .filter_map(|f| {
+            let parent = f.params.get("references").and_then(|v| v.as_str()).filter(|p| *p != template.target_table)?;
+            let keys = parent_keys(parent)?;
+            (rows as usize > keys).then(|| format!(
+                "Унікальний `fk` '{}.{}': {} рядків, а в батьківській таблиці '{}' лише {} ключів. Зменшіть rows для '{}' до {} або збільшіть для '{}'",
+                template.target_table, f.column_name, rows, parent, keys, template.target_table, keys, parent
+            ))
+        })
+        .collect()
+}
+
+/// Кількість рядків з урахуванням `rows_jitter`: випадково в межах `rows ± rows * jitter`.
+/// Зі зерном таблиці результат відтворюваний; таблиця з `rows > 0` не отримує нуль рядків.
+fn jittered_rows(rows: u32, jitter: f64, rng_seed: Option<u64>) -> u32 {
+    let jitter = jitter.clamp(0.0, 1.0);
+    if jitter == 0.0 || rows == 0 {
+        return rows;
+    }
+    let spread = rows as f64 * jitter;
+    let min = (rows as f64 - spread).round().max(1.0) as u32;
+    let max = (rows as f64 + spread).round() as u32;
+    match rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed ^ ROWS_JITTER_SALT).gen_range(min..=max),
+        None => rand::thread_rng().gen_range(min..=max),
+    }
+}
+
+/// Обмежує кількість рядків таблиці `max_rows` (`--max-rows-per-table`) з попередженням
+fn capped_rows(table: &str, rows: u32, max_rows: Option<u32>) -> u32 {
+    match max_rows {
+        Some(max) if rows > max => {
+            warn!("✂️  Таблиця '{}': {} рядків обмежено до {} (max_rows_per_table)", table, rows, max);
+            max
+        }
+        _ => rows,
+    }
+}
+
+/// План з кількістю рядків `Rows::Fixed` для кожної таблиці: `rows_jitter` застосовано, а відносна кількість
+/// оцінена за запланованою кількістю батьківської таблиці (для розміру пулів і загального прогресу)
+fn planned_rows(tasks: &[SeedingTask], global_seed: Option<u64>, max_rows: Option<u32>) -> Vec<SeedingTask> {
+    let mut planned: HashMap<&str, u32> = HashMap::new();
+    for task in tasks {
+        if let Rows::Fixed(rows) = task.rows {
+            let rows = jittered_rows(rows, task.rows_jitter, table_rng_seed(global_seed, task));
+            planned.insert(&task.table, capped_rows(&task.table, rows, max_rows));
+        }
+    }
+    // Ланцюжки на кшталт items -> orders -> users розв'язуються за кілька проходів
+    for _ in 0..tasks.len() {
+        let mut changed = false;
+        for task in tasks {
+            let Some(parent) = task.rows.parent() else { continue };
+            if planned.contains_key(task.table.as_str()) {
+                continue;
+            }
+            if let Some(&parent_rows) = planned.get(parent) {
+                // Лише оцінка: про обмеження попередимо, коли кількість буде відома точно
+                let rows = task.rows.resolve(parent_rows as u64);
+                planned.insert(&task.table, max_rows.map_or(rows, |max| rows.min(max)));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    tasks
+        .iter()
+        .map(|task| SeedingTask { rows: Rows::Fixed(planned.get(task.table.as_str()).copied().unwrap_or(0)), ..task.clone() })
+        .collect()
+}
+
+/// FNV-1a: на відміну від `DefaultHasher`, результат не залежить від версії Rust
+fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Діапазон значень цілочисельного типу Postgres
+fn integer_bounds(data_type: &str) -> Option<(i64, i64)> {
+    match data_type {
+        "smallint" => Some((i16::MIN.into(), i16::MAX.into())),
+        "integer" | "int4" => Some((i32::MIN.into(), i32::MAX.into())),
+        "bigint" => Some((i64::MIN, i64::MAX)),
+        _ => None,
+    }
+}
+
+/// Виправляє параметри `number_range` один раз для всієї таблиці: міняє місцями переплутані `min`/`max`
+/// та обрізає межі до діапазону цілочисельної колонки та її CHECK обмежень, щоб значення їх не порушували
+fn normalize_number_range(table: &str, column: &ColumnSchema, check_bounds: (Option<i64>, Option<i64>), params: &mut HashMap<String, Value>) {
+    let mut min = params.get("min").and_then(|v| v.as_i64()).unwrap_or(0);
+    let mut max = params.get("max").and_then(|v| v.as_i64()).unwrap_or(100);
+    if min > max {
+        warn!("⚠️  number_range для '{}.{}': min ({}) більший за max ({}), міняю їх місцями", table, column.name, min, max);
+        std::mem::swap(&mut min, &mut max);
+    }
+
+    if let Some((lower, upper)) = integer_bounds(&column.data_type) {
+        let (clamped_min, clamped_max) = (min.clamp(lower, upper), max.clamp(lower, upper));
+        if (clamped_min, clamped_max) != (min, max) {
+            warn!(
+                "⚠️  number_range для '{}.{}': діапазон {}..={} виходить за межі типу {}, обрізаю до {}..={}",
+                table, column.name, min, max, column.data_type, clamped_min, clamped_max
+            );
+        }
+        (min, max) = (clamped_min, clamped_max);
+    }
+
+    // CHECK обмеження таблиці чи домену (`qty > 0`); суперечливі межі ігноруємо
+    let (lower, upper) = (check_bounds.0.unwrap_or(i64::MIN), check_bounds.1.unwrap_or(i64::MAX));
+    let (checked_min, checked_max) = if lower <= upper { (min.clamp(lower, upper), max.clamp(lower, upper)) } else { (min, max) };
+    if (checked_min, checked_max) != (min, max) {
+        warn!(
+            "⚠️  number_range для '{}.{}': діапазон {}..={} порушує CHECK обмеження, звужую до {}..={}",
+            table, column.name, min, max, checked_min, checked_max
+        );
+        (min, max) = (checked_min, checked_max);
+    }
+
+    params.insert("min".to_string(), json!(min));
+    params.insert("max".to_string(), json!(max));
+}
+
+/// Скільки різних значень має містити пул: `rows * uniqueness_ratio` для найбільшої таблиці плану,
+/// що бере з нього значення через `from_pool`
+fn required_distinct_values(plan: &ArchitecturalPlan, tasks: &[SeedingTask], pool_name: &str, uniqueness_ratio: f32) -> usize {
+    plan.entity_templates
+        .iter()
+        .filter(|template| {
+            template.fields.iter().any(|f| {
+                f.generator == "from_pool" && f.params.get("pool_name").and_then(|v| v.as_str()) == Some(pool_name)
+            })
+        })
+        .filter_map(|template| tasks.iter().find(|t| t.table == template.target_table))
+        .map(|task| (task.rows.count() as f64 * uniqueness_ratio.clamp(0.0, 1.0) as f64).ceil() as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Пул із заглушок `<pool_name>_1`, `<pool_name>_2`, ... для `--skip-pools`
+fn placeholder_pool(pool_name: &str, size: usize) -> Vec<String> {
+    (1..=size).map(|i| format!("{}_{}", pool_name, i)).collect()
+}
+
+fn distinct_count(values: &[String]) -> usize {
+    values.iter().collect::<HashSet<_>>().len()
+}
+
+/// Застосовує `column_overrides` з конфігурації до шаблонів сутностей, отриманих від Gemini
+fn apply_column_overrides(plan: &mut ArchitecturalPlan, tasks: &[SeedingTask]) -> AppResult<()> {
+    for task in tasks.iter().filter(|t| !t.column_overrides.is_empty()) {
+        let Some(template) = plan.entity_templates.iter_mut().find(|e| e.target_table == task.table) else {
+            warn!("⚠️  column_overrides для '{}' проігноровано: Gemini не створив шаблон для цієї таблиці", task.table);
+            continue;
+        };
+
+        for (column_name, column_override) in &task.column_overrides {
+            let mut params = column_override.params.clone();
+            if let Some(kind) = &column_override.kind {
+                params.entry("type".to_string()).or_insert_with(|| json!(kind));
+            }
+            if !column_override.weights.is_empty() {
+                params.insert("weights".to_string(), json!(column_override.weights));
+            }
+            let generator = match column_override.generator.as_str() {
+                "" if !column_override.weights.is_empty() => "choice".to_string(),
+                "" => return Err(AppError::Custom(format!(
+                    "column_overrides '{}.{}': не вказано `generator`", task.table, column_name
+                ))),
+                other => other.to_string(),
+            };
+
+            let field = if generator == "gemini" {
+                let prompt = column_override.prompt.clone().ok_or_else(|| AppError::Custom(format!(
+                    "column_overrides '{}.{}': генератор `gemini` потребує `prompt`", task.table, column_name
+                )))?;
+                let pool_name = format!("override_{}_{}", task.table, column_name);
+                plan.data_pools.insert(pool_name.clone(), DataPoolConfig {
+                    description: format!("Значення для колонки '{}.{}' з column_overrides", task.table, column_name),
+                    uniqueness_ratio: 0.0,
+                    gemini_prompt_for_pool: prompt,
+                });
+                FieldTemplate {
+                    column_name: column_name.clone(),
+                    generator: "from_pool".to_string(),
+                    params: HashMap::from([("pool_name".to_string(), json!(pool_name))]),
+                }
+            } else {
+                FieldTemplate {
+                    column_name: column_name.clone(),
+                    generator,
+                    params,
+                }
+            };
+
+            debug!("Перевизначаю генератор для '{}.{}': {}", task.table, column_name, field.generator);
+            match template.fields.iter_mut().find(|f| &f.column_name == column_name) {
+                Some(existing) => *existing = field,
+                None => template.fields.push(field),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Тип, до якого приводимо PK у `RETURNING`, щоб прочитати його однаково для будь-якого типу колонки:
+/// усі цілочисельні (включно з serial, oid та доменами над ними) — `bigint`, дробові — `float8`,
+/// решта (текст, uuid тощо) — `text`
+fn pk_return_type(column: &ColumnSchema) -> &'static str {
+    match column.data_type.as_str() {
+        "smallint" | "integer" | "bigint" | "oid" => "bigint",
+        "real" | "double precision" => "float8",
+        _ => "text",
+    }
+}
+
+/// Формує плейсхолдер `$N` з явним приведенням до типу колонки, якщо воно потрібне
+fn placeholder_for(index: usize, col_schema: Option<&ColumnSchema>) -> String {
+    match col_schema.and_then(pg_cast_for) {
+        Some(cast) => format!("${}::{}", index, cast),
+        None => format!("${}", index),
+    }
+}
+
+/// Тип для явного приведення (`$N::type`). `None` — для типів, які біндяться нативно (див. `bind_value`).
+/// Значення для всіх інших колонок передаються текстом, тож Postgres перетворює їх сам.
+fn pg_cast_for(column: &ColumnSchema) -> Option<String> {
+    let cast = match column.data_type.as_str() {
+        "integer" | "bigint" | "smallint" | "boolean" | "real" | "double precision" | "text" | "character varying" | "bytea" => return None,
+        "timestamp with time zone" => "timestamptz",
+        "timestamp without time zone" => "timestamp",
+        "date" => "date",
+        "time without time zone" => "time",
+        "time with time zone" => "timetz",
+        "interval" => "interval",
+        "uuid" => "uuid",
+        "numeric" => "numeric",
+        "inet" => "inet",
+        "cidr" => "cidr",
+        "macaddr" => "macaddr",
+        "json" => "json",
+        "jsonb" => "jsonb",
+        // Текстовий ввід `money` залежить від lc_monetary, а приведення numeric -> money — ні
+        "money" => "numeric::money",
+        // enum, масиви та інші типи приводимо до фактичного типу колонки: "schema"."type"
+        _ => return Some(column.qualified_type()),
+    };
+    Some(cast.to_string())
+}
+
+/// Перетворює JSON-значення в ціле число. `None` — для рядків, що не є числом, та об'єктів/масивів
+fn json_to_i64(val: &Value) -> Option<i64> {
+    if let Some(i) = val.as_i64() {
+        Some(i)
+    } else if let Some(f) = val.as_f64() {
+        // Наприклад, результат `expression` для цілочисельної колонки; `as` насичується на межах i64
+        Some(f.round() as i64)
+    } else if let Some(b) = val.as_bool() {
+        Some(b as i64)
+    } else if let Some(s) = val.as_str() {
+        let s = s.trim();
+        s.parse::<i64>().ok().or_else(|| s.parse::<f64>().ok().filter(|f| f.is_finite()).map(|f| f.round() as i64))
+    } else {
+        None
     }
-}
\ No newline at end of file
+}
+
+/// Ціле значення для цілочисельної колонки, обрізане до меж її типу. Значення, яке не можна
+/// перетворити на число, — помилка плану, а не тихий 0
+fn integer_value(val: &Value, column: &ColumnSchema) -> AppResult<i64> {
+    let value = json_to_i64(val).ok_or_else(|| AppError::Custom(format!(
+        "Значення {} не можна записати в цілочисельну колонку '{}' ({})", val, column.name, column.data_type
+    )))?;
+    Ok(match integer_bounds(&column.data_type) {
+        Some((lower, upper)) => value.clamp(lower, upper),
+        None => value,
+    })
+}
+
+/// Сума для колонки `money`, округлена до копійок. Рядки на кшталт "$1,234.50" (від `constant` чи пулу)
+/// очищуються від символу валюти та роздільників тисяч
+fn money_amount(val: &Value) -> f64 {
+    let amount = match val.as_str() {
+        Some(s) => s.chars().filter(|c| c.is_ascii_digit() || matches!(c, '.' | '-')).collect::<String>().parse().unwrap_or(0.0),
+        None => val.as_f64().unwrap_or(0.0),
+    };
+    (amount * 100.0).round() / 100.0
+}
+
+/// Текстовий літерал масиву Postgres (`{1.5,"a b",NULL}`) для JSON-масиву
+fn pg_array_literal(val: &Value) -> String {
+    let items: Vec<String> = val
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::Null => "NULL".to_string(),
+                    Value::Array(_) => pg_array_literal(item),
+                    Value::Bool(_) | Value::Number(_) => item.to_string(),
+                    Value::String(s) => quote_array_element(s),
+                    Value::Object(_) => quote_array_element(&item.to_string()),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    format!("{{{}}}", items.join(","))
+}
+
+/// Елемент літералу масиву в подвійних лапках з екрануванням
+fn quote_array_element(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Біндить JSON-значення до запиту, приводячи його до типу колонки
+fn bind_value<'q>(query: PgQuery<'q>, val: &'q Value, col_schema: Option<&ColumnSchema>) -> AppResult<PgQuery<'q>> {
+    let Some(schema) = col_schema else {
+        // Якщо схему не знайдено, біндимо як є
+        return Ok(query.bind(val));
+    };
+
+    if val.is_null() {
+        // Явний SQL NULL потрібного типу, щоб Postgres не скаржився на невідповідність типів
+        return Ok(match schema.data_type.as_str() {
+            "smallint" => query.bind(None::<i16>),
+            "integer" | "int4" => query.bind(None::<i32>),
+            "bigint" => query.bind(None::<i64>),
+            "boolean" => query.bind(None::<bool>),
+            "numeric" | "decimal" | "real" | "double precision" | "money" => query.bind(None::<f64>),
+            "bytea" => query.bind(None::<Vec<u8>>),
+            _ => query.bind(None::<String>),
+        });
+    }
+
+    Ok(match schema.data_type.as_str() {
+        // Біндимо цілі числа типом тієї ж ширини, що й колонка; значення поза діапазоном обрізаємо до меж типу
+        "smallint" => query.bind(integer_value(val, schema)? as i16),
+        "integer" | "int4" => query.bind(integer_value(val, schema)? as i32),
+        "bigint" => query.bind(integer_value(val, schema)?),
+        "boolean" => {
+            // Якщо в БД тип boolean
+            let bool_val = if let Some(b) = val.as_bool() {
+                b
+            } else if let Some(i) = val.as_i64() {
+                i != 0
+            } else if let Some(s) = val.as_str() {
+                s == "true" || s == "1"
+            } else {
+                false
+            };
+            query.bind(bool_val)
+        }
+        "numeric" | "decimal" | "real" | "double precision" => {
+            let float_val = if let Some(f) = val.as_f64() {
+                f
+            } else if let Some(s) = val.as_str() {
+                s.parse::<f64>().unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            query.bind(float_val)
+        }
+        "money" => query.bind(money_amount(val)),
+        "bytea" => {
+            // Генератор `bytea` віддає base64; рядок, що не є base64, записуємо як є
+            let bytes = match val.as_str() {
+                Some(s) => BASE64_STANDARD.decode(s).unwrap_or_else(|_| s.as_bytes().to_vec()),
+                None => val.to_string().into_bytes(),
+            };
+            query.bind(bytes)
+        }
+        "json" | "jsonb" => query.bind(val),
+        // JSON-масив (наприклад, `geo_point` з "as": "latlon_pair") перетворюємо на літерал масиву Postgres
+        "ARRAY" if val.is_array() => query.bind(pg_array_literal(val)),
+        _ => {
+            // Для решти типів покладаємося на приведення в SQL (див. `pg_cast_for`) і передаємо рядок;
+            // числа та bool (наприклад, з `constant`) перетворюємо в текст
+            query.bind(val.as_str().map(str::to_string).unwrap_or_else(|| val.to_string()))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(data_type: &str) -> ColumnSchema {
+        ColumnSchema {
+            name: "value".to_string(),
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            column_default: None,
+            udt_schema: "pg_catalog".to_string(),
+            udt_name: data_type.to_string(),
+            enum_labels: Vec::new(),
+            max_length: None,
+            domain: None,
+            db_generated: false,
+            db_generated_always: false,
+        }
+    }
+
+    #[test]
+    fn integer_value_clamps_to_column_type() {
+        let cases = [
+            ("smallint", json!(123), 123),
+            ("smallint", json!(40_000), i16::MAX as i64),
+            ("smallint", json!(-40_000), i16::MIN as i64),
+            ("integer", json!(3_000_000_000u64), i32::MAX as i64),
+            ("integer", json!(-3_000_000_000i64), i32::MIN as i64),
+            ("int4", json!(3_000_000_000u64), i32::MAX as i64),
+            ("bigint", json!(u64::MAX), i64::MAX),
+            ("bigint", json!(i64::MIN), i64::MIN),
+            ("integer", json!(2.5), 3),
+            ("integer", json!(-2.4), -2),
+            ("smallint", json!(1e12), i16::MAX as i64),
+            ("bigint", json!(1e300), i64::MAX),
+            ("integer", json!(true), 1),
+            ("integer", json!(" 42 "), 42),
+            ("integer", json!("7.6"), 8),
+            ("smallint", json!("99999"), i16::MAX as i64),
+        ];
+        for (data_type, value, expected) in cases {
+            assert_eq!(integer_value(&value, &column(data_type)).unwrap(), expected, "{} {}", data_type, value);
+        }
+    }
+
+    #[test]
+    fn integer_value_rejects_non_numbers() {
+        for value in [json!("abc"), json!(""), json!("NaN"), json!([1]), json!({"n": 1})] {
+            let error = integer_value(&value, &column("integer")).unwrap_err();
+            assert!(error.to_string().contains("цілочисельну колонку 'value'"), "{}", error);
+        }
+    }
+
+    /// `min`/`max` після `normalize_number_range`
+    fn normalized(data_type: &str, check_bounds: (Option<i64>, Option<i64>), min: i64, max: i64) -> (i64, i64) {
+        let mut params = HashMap::from([("min".to_string(), json!(min)), ("max".to_string(), json!(max))]);
+        normalize_number_range("items", &column(data_type), check_bounds, &mut params);
+        (params["min"].as_i64().unwrap(), params["max"].as_i64().unwrap())
+    }
+
+    #[test]
+    fn normalize_number_range_swaps_and_clamps() {
+        assert_eq!(normalized("integer", (None, None), 50, 10), (10, 50));
+        assert_eq!(normalized("integer", (None, None), -10, -50), (-50, -10));
+        assert_eq!(normalized("smallint", (None, None), -100_000, 100_000), (i16::MIN as i64, i16::MAX as i64));
+        assert_eq!(normalized("smallint", (None, None), 100_000, 0), (0, i16::MAX as i64));
+        assert_eq!(normalized("integer", (None, None), 0, 5_000_000_000), (0, i32::MAX as i64));
+        assert_eq!(normalized("bigint", (None, None), i64::MIN, i64::MAX), (i64::MIN, i64::MAX));
+        // Нецілочисельні колонки не обрізаються
+        assert_eq!(normalized("numeric", (None, None), -5_000_000_000, 5_000_000_000), (-5_000_000_000, 5_000_000_000));
+    }
+
+    #[test]
+    fn normalize_number_range_respects_check_bounds() {
+        assert_eq!(normalized("integer", (Some(1), None), -10, 10), (1, 10));
+        assert_eq!(normalized("integer", (None, Some(-1)), -10, 10), (-10, -1));
+        assert_eq!(normalized("smallint", (Some(0), Some(100_000)), -5, 50_000), (0, i16::MAX as i64));
+        // Суперечливі межі CHECK ігноруються
+        assert_eq!(normalized("integer", (Some(10), Some(0)), 1, 5), (1, 5));
+    }
+
+    #[test]
+    fn pk_return_type_by_column_type() {
+        let cases = [
+            ("smallint", "bigint"),
+            ("integer", "bigint"),
+            ("bigint", "bigint"),
+            ("oid", "bigint"),
+            ("real", "float8"),
+            ("double precision", "float8"),
+            ("uuid", "text"),
+            ("text", "text"),
+            ("character varying", "text"),
+        ];
+        for (data_type, expected) in cases {
+            assert_eq!(pk_return_type(&column(data_type)), expected, "{}", data_type);
+        }
+
+        // Для домену `data_type` — його базовий тип
+        let mut domain = column("integer");
+        domain.domain = Some(("public".to_string(), "positive_id".to_string()));
+        assert_eq!(pk_return_type(&domain), "bigint");
+        domain.data_type = "text".to_string();
+        domain.domain = Some(("public".to_string(), "sku".to_string()));
+        assert_eq!(pk_return_type(&domain), "text");
+    }
+
+    #[test]
+    fn jittered_rows_is_reproducible_and_bounded() {
+        assert_eq!(jittered_rows(100, 0.0, Some(1)), 100);
+        assert_eq!(jittered_rows(0, 0.5, Some(1)), 0);
+        for seed in 0..50 {
+            let rows = jittered_rows(100, 0.2, Some(seed));
+            assert!((80..=120).contains(&rows), "{}", rows);
+            assert_eq!(rows, jittered_rows(100, 0.2, Some(seed)));
+            assert!(jittered_rows(2, 1.0, Some(seed)) >= 1, "таблиця з rows > 0 не отримує нуль рядків");
+        }
+    }
+}
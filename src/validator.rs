@@ -0,0 +1,286 @@
+// src/validator.rs
+use crate::config::SeedingTask;
+use crate::db::DbSchema;
+use crate::gemini_analyzer::ArchitecturalPlan;
+use petgraph::algo::{tarjan_scc, toposort};
+use petgraph::graphmap::DiGraphMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Перевіряє план заповнення на узгодженість зі схемою БД без звернень до Gemini та без запису даних.
+/// `populated_parents` — таблиці поза планом, що вже містять дані (див. `Seeder::populated_parents`): на них можна посилатися.
+/// Повертає список знайдених проблем (порожній, якщо все гаразд).
+pub fn validate_plan(
+    schema: &DbSchema,
+    full_graph: &DiGraphMap<&str, ()>,
+    plan: &[SeedingTask],
+    populated_parents: &BTreeSet<String>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    // 1. Кожна таблиця з плану має існувати в схемі
+    for table in missing_tables(schema, plan) {
+        problems.push(format!("Таблиця '{}' з плану не існує в схемі БД", table));
+    }
+
+    let tables_in_plan: HashSet<&str> = plan
+        .iter()
+        .map(|t| t.table.as_str())
+        .filter(|t| schema.tables.contains_key(*t))
+        .collect();
+
+    // 2. Граф залежностей таблиць плану має бути ациклічним
+    let mut plan_graph = DiGraphMap::new();
+    for &table_name in &tables_in_plan {
+        plan_graph.add_node(table_name);
+    }
+    for (parent, child, _) in full_graph.all_edges() {
+        if tables_in_plan.contains(parent) && tables_in_plan.contains(child) {
+            plan_graph.add_edge(parent, child, ());
+        }
+    }
+    if toposort(&plan_graph, None).is_err() {
+        let description = describe_cycle(schema, &plan_graph).unwrap_or_default();
+        problems.push(format!("Знайдено циклічну залежність: {}", description));
+    }
+
+    // 3. Покриття NOT NULL колонок
+    for task in plan {
+        let Some(table) = schema.tables.get(&task.table) else { continue };
+
+        for fk in schema.foreign_keys.iter().filter(|fk| fk.from_table == task.table) {
+            if tables_in_plan.contains(fk.to_table.as_str()) || populated_parents.contains(&fk.to_table) {
+                continue;
+            }
+            let is_required = table
+                .columns
+                .iter()
+                .any(|c| c.name == fk.from_column && !c.is_nullable && !c.has_db_default());
+            if is_required {
+                problems.push(format!(
+                    "NOT NULL колонка '{}.{}' посилається на '{}.{}', але таблиці '{}' немає в плані і вона порожня",
+                    fk.from_table, fk.from_column, fk.to_table, fk.to_column, fk.to_table
+                ));
+            }
+        }
+
+        for column_name in task.column_overrides.keys() {
+            if !table.columns.iter().any(|c| &c.name == column_name) {
+                problems.push(format!(
+                    "column_overrides для таблиці '{}' посилається на неіснуючу колонку '{}'",
+                    task.table, column_name
+                ));
+            }
+        }
+    }
+
+    // 4. Батьківські таблиці мають мати первинний ключ, інакше посилання на них будуть NULL
+    problems.extend(unreferenceable_parents(schema, plan));
+
+    // 5. `rows = "3x orders"` може посилатися лише на іншу таблицю цього ж плану
+    problems.extend(invalid_relative_rows(plan));
+
+    // 6. Самопосилання можна заповнити лише nullable колонкою: першому рядку нема на кого послатися
+    problems.extend(required_self_references(schema, plan));
+
+    problems
+}
+
+/// NOT NULL колонки без DEFAULT, що посилаються на PK власної таблиці (наприклад, `categories.parent_id`).
+/// Сідер зв'язує такі рядки вже після вставки, а перший рядок NOT NULL колонки не матиме батька взагалі
+pub fn required_self_references(schema: &DbSchema, plan: &[SeedingTask]) -> Vec<String> {
+    let tables_in_plan: HashSet<&str> = plan.iter().map(|t| t.table.as_str()).collect();
+    schema
+        .foreign_keys
+        .iter()
+        .filter(|fk| fk.from_table == fk.to_table && tables_in_plan.contains(fk.from_table.as_str()))
+        .filter(|fk| {
+            schema.tables.get(&fk.from_table).and_then(|t| t.columns.iter().find(|c| c.name == fk.from_column))
+                .is_some_and(|c| !c.is_nullable && !c.has_db_default())
+        })
+        .map(|fk| {
+            format!(
+                "NOT NULL колонка '{}.{}' посилається на власну таблицю: перший рядок не матиме на кого послатися. Зробіть її nullable (NULL для кореневих рядків)",
+                fk.from_table, fk.from_column
+            )
+        })
+        .collect()
+}
+
+/// Описує один із циклів графа залежностей (ребро батько -> дитина): шлях по зовнішніх ключах
+/// та FK, який варто зробити nullable, щоб розірвати цикл. `None`, якщо граф ациклічний.
+pub fn describe_cycle(schema: &DbSchema, graph: &DiGraphMap<&str, ()>) -> Option<String> {
+    let component = tarjan_scc(graph).into_iter().filter(|c| c.len() > 1).min_by_key(|c| c.len())?;
+    let members: HashSet<&str> = component.iter().copied().collect();
+    // Починаємо з першої за алфавітом таблиці, щоб повідомлення було стабільним між запусками
+    let start = *component.iter().min()?;
+
+    // Пошук у ширину всередині компоненти: найкоротший шлях від `start` назад до неї
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    let mut last = None;
+    while let Some(node) = queue.pop_front() {
+        let mut children: Vec<&str> = graph.neighbors(node).filter(|n| members.contains(n)).collect();
+        children.sort_unstable();
+        if children.contains(&start) {
+            last = Some(node);
+            break;
+        }
+        for child in children {
+            if child != start && !previous.contains_key(child) {
+                previous.insert(child, node);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    // Шлях у напрямку посилань: кожна таблиця посилається на наступну
+    let mut path = vec![start];
+    let mut node = last?;
+    while node != start {
+        path.push(node);
+        node = previous[node];
+    }
+    path.push(start);
+
+    let links: Vec<_> = path
+        .windows(2)
+        .filter_map(|pair| schema.foreign_keys.iter().find(|fk| fk.from_table == pair[0] && fk.to_table == pair[1]))
+        .collect();
+    let is_nullable = |table: &str, column: &str| {
+        schema.tables.get(table).and_then(|t| t.columns.iter().find(|c| c.name == column)).is_some_and(|c| c.is_nullable)
+    };
+    let described: Vec<String> = links.iter().map(|fk| format!("{}.{} -> {}", fk.from_table, fk.from_column, fk.to_table)).collect();
+    let mut message = format!("{} ({})", path.join(" -> "), described.join(", "));
+    if let Some(fk) = links.iter().find(|fk| !is_nullable(&fk.from_table, &fk.from_column)).or(links.first()) {
+        message.push_str(&format!(
+            ". Щоб розірвати цикл, зробіть FK '{}.{}' nullable або приберіть одну з таблиць циклу з плану",
+            fk.from_table, fk.from_column
+        ));
+    }
+    Some(message)
+}
+
+/// Розбіжності між шаблонами плану (збереженого через `--plan` чи щойно отриманого) та поточною схемою БД:
+/// колонки, яких уже немає; NOT NULL колонки без значення за замовчуванням, які шаблон не заповнює;
+/// генератори, чиї значення не приведуться до типу колонки. Колонки з `column_overrides` вважаються покритими.
+/// Будь-який DEFAULT (не лише `nextval`), IDENTITY та обчислювані колонки заповнить сама БД.
+pub fn plan_drift(schema: &DbSchema, plan: &ArchitecturalPlan, tasks: &[SeedingTask]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for template in &plan.entity_templates {
+        let Some(task) = tasks.iter().find(|t| t.table == template.target_table) else { continue };
+        let Some(table) = schema.tables.get(&template.target_table) else {
+            problems.push(format!("Таблиці '{}' з шаблону '{}' немає в схемі БД", template.target_table, template.entity_name));
+            continue;
+        };
+
+        for field in &template.fields {
+            match table.columns.iter().find(|c| c.name == field.column_name) {
+                None => problems.push(format!("Колонки '{}.{}' з плану немає в схемі БД", table.name, field.column_name)),
+                Some(column) => {
+                    let expected = column_value_kind(&column.data_type);
+                    if let Some(produced) = generator_value_kind(&field.generator).filter(|k| expected.is_some_and(|e| e != *k)) {
+                        problems.push(format!(
+                            "'{}.{}' має тип {}, а генератор `{}` створює {}",
+                            table.name, column.name, column.data_type, field.generator, produced
+                        ));
+                    }
+                }
+            }
+        }
+
+        let covered = |name: &str| template.fields.iter().any(|f| f.column_name == name) || task.column_overrides.contains_key(name);
+        for column in table.columns.iter().filter(|c| !c.is_nullable && !c.has_db_default() && !covered(&c.name)) {
+            problems.push(format!(
+                "NOT NULL колонка '{}.{}' без DEFAULT не заповнюється шаблоном '{}'",
+                table.name, column.name, template.entity_name
+            ));
+        }
+    }
+    problems
+}
+
+/// Які значення приймає колонка: `None` — будь-які (текст, enum та інші типи з текстовим вводом)
+fn column_value_kind(data_type: &str) -> Option<&'static str> {
+    match data_type {
+        "integer" | "bigint" | "smallint" | "numeric" | "real" | "double precision" | "money" => Some("числа"),
+        "boolean" => Some("логічні значення"),
+        "date" | "timestamp with time zone" | "timestamp without time zone" | "time with time zone" | "time without time zone" => Some("дати й час"),
+        "json" | "jsonb" => Some("JSON"),
+        _ => None,
+    }
+}
+
+/// Які значення створює вбудований генератор: `None`, якщо це залежить від параметрів чи даних (`template`, `fk`, `from_pool`...)
+fn generator_value_kind(generator: &str) -> Option<&'static str> {
+    match generator {
+        "number_range" | "sequence" => Some("числа"),
+        "boolean" => Some("логічні значення"),
+        "datetime_range" | "time_range" => Some("дати й час"),
+        "json_array" => Some("JSON"),
+        "words" | "sentence" | "paragraphs" | "company" | "job_title" | "url" | "slug" | "hex_color" | "currency_code"
+        | "country_code" | "password_hash" | "pk_hash" | "interval" => Some("текст"),
+        _ => None,
+    }
+}
+
+/// Посилання `from_pool` (зокрема елементів `json_array`) на пули, яких немає в `data_pools` плану.
+/// Перевіряється до заповнення пулів, щоб не витрачати запити до Gemini на внутрішньо неузгоджений план.
+pub fn dangling_pool_references(plan: &ArchitecturalPlan) -> Vec<String> {
+    let mut problems = Vec::new();
+    for template in &plan.entity_templates {
+        for field in &template.fields {
+            let pool_name = match field.generator.as_str() {
+                "from_pool" => field.params.get("pool_name"),
+                "json_array" if field.params.get("element_generator").and_then(|v| v.as_str()) == Some("from_pool") => {
+                    field.params.get("element_params").and_then(|p| p.get("pool_name"))
+                }
+                _ => continue,
+            };
+            match pool_name.and_then(|v| v.as_str()) {
+                Some(name) if plan.data_pools.contains_key(name) => {}
+                Some(name) => problems.push(format!(
+                    "'{}.{}' посилається на пул '{}', якого немає в data_pools плану",
+                    template.target_table, field.column_name, name
+                )),
+                None => problems.push(format!("'{}.{}': `from_pool` без `pool_name`", template.target_table, field.column_name)),
+            }
+        }
+    }
+    problems
+}
+
+/// Відносні `rows` ("3x orders"), батьківської таблиці яких немає в плані, або що посилаються на саму таблицю
+pub fn invalid_relative_rows(plan: &[SeedingTask]) -> Vec<String> {
+    plan.iter()
+        .filter_map(|task| task.rows.parent().map(|parent| (task, parent)))
+        .filter(|(task, parent)| *parent == task.table || !plan.iter().any(|t| t.table == *parent))
+        .map(|(task, parent)| format!(
+            "rows = \"{}\" для таблиці '{}': таблиця '{}' має бути іншою таблицею цього ж плану",
+            task.rows, task.table, parent
+        ))
+        .collect()
+}
+
+/// Таблиці плану, яких немає в схемі БД (опечатка в назві, видалена таблиця)
+pub fn missing_tables<'a>(schema: &DbSchema, plan: &'a [SeedingTask]) -> Vec<&'a str> {
+    plan.iter().map(|t| t.table.as_str()).filter(|t| !schema.tables.contains_key(*t)).collect()
+}
+
+/// Зовнішні ключі на таблиці плану без первинного ключа. Сідер отримує ключі вставлених рядків лише
+/// через `RETURNING` PK, тож посилання на такі таблиці стали б NULL. Таблиці без PK, на які
+/// ніхто не посилається (журнали, аудит), заповнюються як звичайно.
+pub fn unreferenceable_parents(schema: &DbSchema, plan: &[SeedingTask]) -> Vec<String> {
+    let tables_in_plan: HashSet<&str> = plan.iter().map(|t| t.table.as_str()).collect();
+    schema
+        .foreign_keys
+        .iter()
+        .filter(|fk| tables_in_plan.contains(fk.from_table.as_str()) && tables_in_plan.contains(fk.to_table.as_str()))
+        .filter(|fk| schema.tables.get(&fk.to_table).is_some_and(|t| t.primary_key_column.is_none()))
+        .map(|fk| {
+            format!(
+                "'{}.{}' посилається на таблицю '{}', яка не має первинного ключа: сідер не може отримати її ключі. Додайте PK до '{}' або приберіть '{}' з плану",
+                fk.from_table, fk.from_column, fk.to_table, fk.to_table, fk.from_table
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,65 @@
+//! Спільне для інтеграційних тестів: кожен тест працює в окремій схемі Postgres з `DATABASE_URL`.
+//! Без `DATABASE_URL` тести, що потребують БД, пропускаються.
+#![allow(dead_code)]
+
+use db_seeder::{AppConfig, ArchitecturalPlan, RunSummary};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Connection, PgConnection};
+
+pub struct TestDb {
+    pub url: String,
+    pub schema: String,
+    pub pool: PgPool,
+}
+
+impl TestDb {
+    /// Створює порожню схему `db_seeder_test_<name>` і виконує в ній `ddl`. `None`, якщо `DATABASE_URL` не задано
+    pub async fn setup(name: &str, ddl: &str) -> Option<Self> {
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            eprintln!("DATABASE_URL не задано: тест '{}' пропущено", name);
+            return None;
+        };
+        let schema = format!("db_seeder_test_{}", name);
+
+        // DDL виконуємо через окреме з'єднання, щоб `search_path` не потрапив у пул сідера
+        let mut conn = PgConnection::connect(&url).await.expect("підключення до DATABASE_URL");
+        let script = format!(
+            "DROP SCHEMA IF EXISTS \"{0}\" CASCADE; CREATE SCHEMA \"{0}\"; SET search_path TO \"{0}\"; {1}",
+            schema, ddl
+        );
+        sqlx::raw_sql(&script).execute(&mut conn).await.expect("створення тестової схеми");
+        conn.close().await.expect("закриття з'єднання");
+
+        let pool = PgPoolOptions::new().max_connections(2).connect(&url).await.expect("пул з'єднань");
+        Some(Self { url, schema, pool })
+    }
+
+    /// Записує конфігурацію з `[database]` цієї схеми та переданими секціями і завантажує її як `db_seeder -c`
+    pub fn config(&self, sections: &str) -> AppConfig {
+        let path = std::env::temp_dir().join(format!("{}.toml", self.schema));
+        let contents = format!("[database]\nurl = \"{}\"\nschema = \"{}\"\n\n{}", self.url, self.schema, sections);
+        std::fs::write(&path, contents).expect("запис тестової конфігурації");
+        let config = AppConfig::from_file(path.to_str().unwrap(), None).expect("тестова конфігурація");
+        std::fs::remove_file(&path).ok();
+        config
+    }
+
+    /// Заповнює схему за планом у форматі `plan --output`
+    pub async fn seed(&self, config: &AppConfig, plan_json: &str) -> db_seeder::AppResult<RunSummary> {
+        let plan: ArchitecturalPlan = serde_json::from_str(plan_json).expect("тестовий план");
+        db_seeder::seed_with_plan(self.pool.clone(), config, plan).await
+    }
+
+    /// Повне ім'я таблиці тестової схеми для SQL у перевірках
+    pub fn table(&self, name: &str) -> String {
+        format!("\"{}\".\"{}\"", self.schema, name)
+    }
+
+    pub async fn teardown(self) {
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", self.schema))
+            .execute(&self.pool)
+            .await
+            .expect("видалення тестової схеми");
+        self.pool.close().await;
+    }
+}
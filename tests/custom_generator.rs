@@ -0,0 +1,72 @@
+//! Користувацькі генератори: перевірка окремо через `GeneratorContext::for_test` та явний NULL через `ctx.null()`
+mod common;
+
+use common::TestDb;
+use db_seeder::{AppResult, ArchitecturalPlan, DbClient, GeneratorContext, RunOptions, Seeder};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// `INV-<рік>-<номер рядка>`; без `year` — явний NULL
+fn invoice_number(ctx: &mut GeneratorContext) -> AppResult<Value> {
+    let Some(year) = ctx.params.get("year").and_then(|v| v.as_u64()) else {
+        return Ok(ctx.null());
+    };
+    let check: u32 = ctx.rng.gen_range(0..10);
+    Ok(json!(format!("INV-{}-{:05}-{}", year, ctx.row_index + 1, check)))
+}
+
+#[test]
+fn custom_generator_in_isolation() {
+    let params = HashMap::from([("year".to_string(), json!(2024))]);
+    let (row, pools) = (HashMap::new(), HashMap::new());
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut ctx = GeneratorContext::for_test("number", &params, &row, &pools, &mut rng);
+    ctx.row_index = 41;
+
+    let value = invoice_number(&mut ctx).unwrap();
+    assert!(value.as_str().unwrap().starts_with("INV-2024-00042-"), "{}", value);
+    assert!(!ctx.is_explicit_null());
+
+    let no_params = HashMap::new();
+    let mut ctx = GeneratorContext::for_test("number", &no_params, &row, &pools, &mut rng);
+    assert_eq!(invoice_number(&mut ctx).unwrap(), Value::Null);
+    assert!(ctx.is_explicit_null());
+}
+
+#[tokio::test]
+async fn explicit_null_overrides_db_default() {
+    let Some(db) = TestDb::setup(
+        "custom_generator",
+        "CREATE TABLE invoices (id serial PRIMARY KEY, skipped text DEFAULT 'default', cleared text DEFAULT 'default');",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config("[generation]\nlanguage = \"en\"\n\n[[seeding_plan]]\ntable = \"invoices\"\nrows = 3\n");
+    let plan = r#"{"theme":"billing","data_pools":{},"entity_templates":[
+        {"entity_name":"Invoice","target_table":"invoices","fields":[
+            {"column_name":"skipped","generator":"omit","params":{}},
+            {"column_name":"cleared","generator":"invoice_number","params":{}}
+        ]}
+    ]}"#;
+
+    let mut seeder = Seeder::new(DbClient::from_pool(db.pool.clone()).with_schema(&db.schema)).await.unwrap();
+    seeder.register_generator("invoice_number", invoice_number);
+    seeder.register_generator("omit", |_: &mut GeneratorContext| Ok(Value::Null));
+    let options = RunOptions {
+        architectural_plan: Some(serde_json::from_str::<ArchitecturalPlan>(plan).unwrap()),
+        ..RunOptions::default()
+    };
+    seeder.run(&config, &options).await.expect("заповнення з користувацькими генераторами");
+
+    let rows: Vec<(Option<String>, Option<String>)> = sqlx::query_as(&format!("SELECT skipped, cleared FROM {}", db.table("invoices")))
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![(Some("default".to_string()), None); 3]);
+
+    db.teardown().await;
+}
@@ -0,0 +1,72 @@
+//! Колонки, оголошені через DOMAIN: сідер працює з базовим типом домену та враховує його CHECK
+mod common;
+
+use common::TestDb;
+
+#[tokio::test]
+async fn seeds_domain_columns() {
+    let Some(db) = TestDb::setup(
+        "domain_columns",
+        "CREATE DOMAIN sku AS text CHECK (VALUE ~ '^SKU-[0-9]+$');
+         CREATE DOMAIN email_address AS text CHECK (VALUE LIKE '%@%');
+         CREATE DOMAIN positive_qty AS integer CHECK (VALUE > 0);
+         CREATE TABLE products (code sku PRIMARY KEY, contact email_address NOT NULL, stock positive_qty NOT NULL);
+         CREATE TABLE order_lines (id serial PRIMARY KEY, product_code sku NOT NULL REFERENCES products(code), qty positive_qty NOT NULL);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config(
+        r#"[generation]
+language = "en"
+seed = 13
+
+[[seeding_plan]]
+table = "products"
+rows = 15
+
+[[seeding_plan]]
+table = "order_lines"
+rows = 40
+"#,
+    );
+    // `min` нижчий за CHECK домену: сідер має звузити діапазон до `VALUE > 0`
+    let plan = r#"{"theme":"warehouse","data_pools":{},"entity_templates":[
+        {"entity_name":"Product","target_table":"products","fields":[
+            {"column_name":"code","generator":"template","params":{"format":"SKU-{random_digits:9}"}},
+            {"column_name":"contact","generator":"template","params":{"format":"buyer{random_digits:6}@example.com"}},
+            {"column_name":"stock","generator":"number_range","params":{"min":-50,"max":500}}
+        ]},
+        {"entity_name":"OrderLine","target_table":"order_lines","fields":[
+            {"column_name":"product_code","generator":"fk","params":{"references":"products"}},
+            {"column_name":"qty","generator":"number_range","params":{"min":-10,"max":10}}
+        ]}
+    ]}"#;
+
+    let summary = db.seed(&config, plan).await.expect("заповнення доменних колонок");
+    assert_eq!(summary.tables.iter().map(|t| t.inserted).collect::<Vec<_>>(), vec![15, 40]);
+
+    let (min_stock, min_qty): (i32, i32) = sqlx::query_as(&format!(
+        "SELECT (SELECT MIN(stock) FROM {}), (SELECT MIN(qty) FROM {})",
+        db.table("products"),
+        db.table("order_lines")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(min_stock > 0 && min_qty > 0, "{} {}", min_stock, min_qty);
+
+    // PK з домену над text повертається з RETURNING і використовується для посилань
+    let orphans: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} l LEFT JOIN {} p ON p.code = l.product_code WHERE p.code IS NULL",
+        db.table("order_lines"),
+        db.table("products")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(orphans, 0);
+
+    db.teardown().await;
+}
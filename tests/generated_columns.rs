@@ -0,0 +1,74 @@
+//! `GENERATED ALWAYS` колонки (identity та обчислювані) ніколи не потрапляють в INSERT,
+//! а `GENERATED BY DEFAULT AS IDENTITY` приймає значення з плану
+mod common;
+
+use common::TestDb;
+
+const DDL: &str = "CREATE TABLE order_items (
+    id integer GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    line_no integer GENERATED BY DEFAULT AS IDENTITY,
+    price integer NOT NULL,
+    qty integer NOT NULL,
+    total integer GENERATED ALWAYS AS (price * qty) STORED,
+    created_at timestamptz NOT NULL DEFAULT now()
+);";
+
+const PLAN: &str = r#"{"theme":"shop","data_pools":{},"entity_templates":[
+    {"entity_name":"OrderItem","target_table":"order_items","fields":[
+        {"column_name":"id","generator":"sequence","params":{}},
+        {"column_name":"line_no","generator":"sequence","params":{"start":1000}},
+        {"column_name":"price","generator":"number_range","params":{"min":1,"max":100}},
+        {"column_name":"qty","generator":"number_range","params":{"min":1,"max":5}},
+        {"column_name":"total","generator":"expression","params":{"formula":"price * qty"}},
+        {"column_name":"created_at","generator":"datetime_range","params":{"start":"2001-01-01","end":"2001-12-31"}}
+    ]}
+]}"#;
+
+async fn seed_order_items(name: &str, generation: &str) -> Option<(TestDb, Vec<(i32, i32, i32, i32, i32, i32)>)> {
+    let db = TestDb::setup(name, DDL).await?;
+    let config = db.config(&format!(
+        "[generation]\nlanguage = \"en\"\nseed = 17\n{}\n\n[[seeding_plan]]\ntable = \"order_items\"\nrows = 10\n\n[seeding_plan.column_overrides.total]\ngenerator = \"constant\"\nparams = {{ value = 1 }}\n",
+        generation
+    ));
+    let summary = db.seed(&config, PLAN).await.expect("заповнення таблиці з GENERATED колонками");
+    assert_eq!(summary.tables[0].inserted, 10);
+
+    let rows = sqlx::query_as(&format!(
+        "SELECT id, line_no, price, qty, total, EXTRACT(YEAR FROM created_at)::int4 FROM {} ORDER BY id",
+        db.table("order_items")
+    ))
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    Some((db, rows))
+}
+
+#[tokio::test]
+async fn skips_generated_always_columns() {
+    let Some((db, rows)) = seed_order_items("generated_always", "").await else {
+        return;
+    };
+    for (i, &(id, line_no, price, qty, total, year)) in rows.iter().enumerate() {
+        assert_eq!(id, i as i32 + 1, "id видає IDENTITY");
+        assert_eq!(line_no, 1000 + i as i32, "BY DEFAULT identity приймає значення з плану");
+        assert_eq!(total, price * qty, "total обчислює БД, а не column_overrides");
+        assert_eq!(year, 2001);
+    }
+    db.teardown().await;
+}
+
+#[tokio::test]
+async fn prefer_db_defaults_keeps_generated_always_skipped() {
+    let Some((db, rows)) = seed_order_items("generated_always_defaults", "prefer_db_defaults = true").await else {
+        return;
+    };
+    let current_year = rows[0].5;
+    for (i, &(id, line_no, price, qty, total, year)) in rows.iter().enumerate() {
+        assert_eq!(id, i as i32 + 1);
+        assert_eq!(line_no, i as i32 + 1, "з prefer_db_defaults line_no заповнює БД");
+        assert_eq!(total, price * qty);
+        assert_eq!(year, current_year, "created_at отримує now()");
+    }
+    assert_ne!(current_year, 2001);
+    db.teardown().await;
+}
@@ -0,0 +1,76 @@
+//! Цілочисельні колонки біндяться типом своєї ширини (i16/i32/i64), а значення поза діапазоном обрізаються до меж типу
+mod common;
+
+use common::TestDb;
+
+/// Шаблон з однаковими `constant` для колонок `s smallint`, `i integer`, `b bigint`
+fn template(table: &str, s: &str, i: &str, b: &str) -> String {
+    format!(
+        r#"{{"entity_name":"{0}","target_table":"{0}","fields":[
+            {{"column_name":"s","generator":"constant","params":{{"value":{1}}}}},
+            {{"column_name":"i","generator":"constant","params":{{"value":{2}}}}},
+            {{"column_name":"b","generator":"constant","params":{{"value":{3}}}}}
+        ]}}"#,
+        table, s, i, b
+    )
+}
+
+#[tokio::test]
+async fn binds_each_integer_width() {
+    let tables = ["minimums", "maximums", "overflow", "strings"];
+    let ddl: String = tables
+        .iter()
+        .map(|t| format!("CREATE TABLE {} (id serial PRIMARY KEY, s smallint NOT NULL, i integer NOT NULL, b bigint NOT NULL);", t))
+        .collect();
+    let Some(db) = TestDb::setup("integer_widths", &ddl).await else {
+        return;
+    };
+    let sections: String = tables.iter().map(|t| format!("[[seeding_plan]]\ntable = \"{}\"\nrows = 1\n\n", t)).collect();
+    let config = db.config(&format!("[generation]\nlanguage = \"en\"\n\n{}", sections));
+    let templates = [
+        template("minimums", "-32768", "-2147483648", "-9223372036854775808"),
+        template("maximums", "32767", "2147483647", "9223372036854775807"),
+        template("overflow", "40000", "-3000000000", "1e300"),
+        template("strings", "\" 12 \"", "\"7.6\"", "\"-5\""),
+    ];
+    let plan = format!(r#"{{"theme":"numbers","data_pools":{{}},"entity_templates":[{}]}}"#, templates.join(","));
+
+    let summary = db.seed(&config, &plan).await.expect("заповнення цілочисельних колонок");
+    assert_eq!(summary.tables.iter().map(|t| t.inserted).sum::<u64>(), 4);
+
+    let mut rows = Vec::new();
+    for table in tables {
+        let row: (i16, i32, i64) = sqlx::query_as(&format!("SELECT s, i, b FROM {}", db.table(table))).fetch_one(&db.pool).await.unwrap();
+        rows.push(row);
+    }
+    assert_eq!(
+        rows,
+        vec![
+            (i16::MIN, i32::MIN, i64::MIN),
+            (i16::MAX, i32::MAX, i64::MAX),
+            (i16::MAX, i32::MIN, i64::MAX),
+            (12, 8, -5),
+        ]
+    );
+
+    db.teardown().await;
+}
+
+#[tokio::test]
+async fn rejects_non_numeric_value() {
+    let Some(db) = TestDb::setup(
+        "integer_widths_invalid",
+        "CREATE TABLE counters (id serial PRIMARY KEY, s smallint NOT NULL, i integer NOT NULL, b bigint NOT NULL);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config("[generation]\nlanguage = \"en\"\n\n[[seeding_plan]]\ntable = \"counters\"\nrows = 1\n");
+    let plan = format!(r#"{{"theme":"numbers","data_pools":{{}},"entity_templates":[{}]}}"#, template("counters", "1", "\"abc\"", "3"));
+
+    let error = db.seed(&config, &plan).await.expect_err("\"abc\" не є числом");
+    assert!(error.to_string().contains("цілочисельну колонку 'i' (integer)"), "{}", error);
+
+    db.teardown().await;
+}
@@ -0,0 +1,41 @@
+//! Колонки `interval`: генератор `interval` віддає текст `'<n> seconds'`, який сідер приводить через `::interval`
+mod common;
+
+use common::TestDb;
+
+#[tokio::test]
+async fn seeds_interval_column() {
+    let Some(db) = TestDb::setup(
+        "interval",
+        "CREATE TABLE shifts (id serial PRIMARY KEY, duration interval NOT NULL, break_time interval);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config("[generation]\nlanguage = \"en\"\nseed = 3\n\n[[seeding_plan]]\ntable = \"shifts\"\nrows = 30\n");
+    let plan = r#"{"theme":"scheduling","data_pools":{},"entity_templates":[
+        {"entity_name":"Shift","target_table":"shifts","fields":[
+            {"column_name":"duration","generator":"interval","params":{"min_secs":3600,"max_secs":28800}},
+            {"column_name":"break_time","generator":"interval","params":{"min_secs":900,"max_secs":0}}
+        ]}
+    ]}"#;
+
+    let summary = db.seed(&config, plan).await.expect("заповнення interval колонок");
+    assert_eq!(summary.tables[0].inserted, 30);
+
+    let (min_duration, max_duration, min_break, max_break): (f64, f64, f64, f64) = sqlx::query_as(&format!(
+        "SELECT MIN(EXTRACT(EPOCH FROM duration))::float8, MAX(EXTRACT(EPOCH FROM duration))::float8,
+                MIN(EXTRACT(EPOCH FROM break_time))::float8, MAX(EXTRACT(EPOCH FROM break_time))::float8 FROM {}",
+        db.table("shifts")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(min_duration >= 3600.0 && max_duration <= 28800.0, "{}..{}", min_duration, max_duration);
+    assert!(min_duration < max_duration, "значення мають відрізнятися");
+    // Переплутані min_secs/max_secs міняються місцями
+    assert!(min_break >= 0.0 && max_break <= 900.0, "{}..{}", min_break, max_break);
+
+    db.teardown().await;
+}
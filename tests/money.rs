@@ -0,0 +1,44 @@
+//! Колонки `money`: суми з `number_range` та рядки з символом валюти приводяться через `numeric::money`
+mod common;
+
+use common::TestDb;
+
+#[tokio::test]
+async fn seeds_money_column() {
+    let Some(db) = TestDb::setup(
+        "money",
+        "CREATE TABLE invoices (id serial PRIMARY KEY, total money NOT NULL, fee money NOT NULL, refund money);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config("[generation]\nlanguage = \"en\"\nseed = 5\n\n[[seeding_plan]]\ntable = \"invoices\"\nrows = 25\n");
+    let plan = r#"{"theme":"billing","data_pools":{},"entity_templates":[
+        {"entity_name":"Invoice","target_table":"invoices","fields":[
+            {"column_name":"total","generator":"number_range","params":{"min":10,"max":500,"decimals":2}},
+            {"column_name":"fee","generator":"constant","params":{"value":"$1,234.56"}},
+            {"column_name":"refund","generator":"number_range","params":{"min":-20,"max":-1}}
+        ]}
+    ]}"#;
+
+    let summary = db.seed(&config, plan).await.expect("заповнення money колонок");
+    assert_eq!(summary.tables[0].inserted, 25);
+
+    let rows: Vec<(f64, f64, f64)> = sqlx::query_as(&format!(
+        "SELECT total::numeric::float8, fee::numeric::float8, refund::numeric::float8 FROM {}",
+        db.table("invoices")
+    ))
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(rows.len(), 25);
+    for (total, fee, refund) in rows {
+        assert!((10.0..=500.0).contains(&total), "{}", total);
+        assert_eq!((total * 100.0).round() / 100.0, total, "сума має бути в копійках");
+        assert_eq!(fee, 1234.56);
+        assert!((-20.0..=-1.0).contains(&refund), "{}", refund);
+    }
+
+    db.teardown().await;
+}
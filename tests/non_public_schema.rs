@@ -0,0 +1,64 @@
+//! `database.schema`: рефлексія та вставка працюють у вказаній схемі, а не в `public`
+mod common;
+
+use common::TestDb;
+
+#[tokio::test]
+async fn seeds_tables_outside_public() {
+    // Таблиця з тим самим ім'ям у `public` має інші колонки: якщо сідер звернеться до неї, вставка впаде
+    let Some(db) = TestDb::setup(
+        "non_public_schema",
+        "CREATE TABLE ns_customers (id serial PRIMARY KEY, email text NOT NULL UNIQUE);
+         CREATE TABLE ns_orders (id bigserial PRIMARY KEY, customer_id integer NOT NULL REFERENCES ns_customers(id), total integer NOT NULL);
+         DROP TABLE IF EXISTS public.ns_customers;
+         CREATE TABLE public.ns_customers (id serial PRIMARY KEY, legacy_code text NOT NULL);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config(
+        r#"[generation]
+language = "en"
+seed = 11
+
+[[seeding_plan]]
+table = "ns_customers"
+rows = 10
+
+[[seeding_plan]]
+table = "ns_orders"
+rows = "3x ns_customers"
+"#,
+    );
+    let plan = r#"{"theme":"shop","data_pools":{},"entity_templates":[
+        {"entity_name":"Customer","target_table":"ns_customers","fields":[
+            {"column_name":"email","generator":"template","params":{"format":"user{random_digits:8}@example.com"}}
+        ]},
+        {"entity_name":"Order","target_table":"ns_orders","fields":[
+            {"column_name":"customer_id","generator":"fk","params":{"references":"ns_customers"}},
+            {"column_name":"total","generator":"number_range","params":{"min":1,"max":1000}}
+        ]}
+    ]}"#;
+
+    let result = db.seed(&config, plan).await;
+    let public_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM public.ns_customers").fetch_one(&db.pool).await.unwrap();
+    sqlx::query("DROP TABLE public.ns_customers").execute(&db.pool).await.unwrap();
+    let summary = result.expect("заповнення не-public схеми");
+
+    assert_eq!(public_rows, 0, "таблиця в public не мала змінитися");
+    assert_eq!(summary.tables.iter().map(|t| t.inserted).collect::<Vec<_>>(), vec![10, 30]);
+
+    // Кожне замовлення посилається на клієнта з тієї ж схеми
+    let orphans: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} o LEFT JOIN {} c ON c.id = o.customer_id WHERE c.id IS NULL",
+        db.table("ns_orders"),
+        db.table("ns_customers")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(orphans, 0);
+
+    db.teardown().await;
+}
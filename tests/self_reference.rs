@@ -0,0 +1,88 @@
+//! Самопосилальні таблиці: дерево категорій через nullable `parent_id`
+mod common;
+
+use common::TestDb;
+
+const PLAN: &str = r#"{"theme":"shop","data_pools":{},"entity_templates":[
+    {"entity_name":"Category","target_table":"categories","fields":[
+        {"column_name":"name","generator":"template","params":{"format":"category-{random_digits:6}"}}
+    ]}
+]}"#;
+
+#[tokio::test]
+async fn seeds_category_tree() {
+    let Some(db) = TestDb::setup(
+        "category_tree",
+        "CREATE TABLE categories (id serial PRIMARY KEY, parent_id integer REFERENCES categories(id), name text NOT NULL);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config(
+        r#"[generation]
+language = "en"
+seed = 7
+
+[[seeding_plan]]
+table = "categories"
+rows = 40
+self_reference_chance = 0.7
+"#,
+    );
+
+    let summary = db.seed(&config, PLAN).await.expect("заповнення дерева категорій");
+    assert_eq!(summary.tables[0].inserted, 40);
+
+    let (total, roots, children): (i64, i64, i64) = sqlx::query_as(&format!(
+        "SELECT COUNT(*), COUNT(*) FILTER (WHERE parent_id IS NULL), COUNT(*) FILTER (WHERE parent_id IS NOT NULL) FROM {}",
+        db.table("categories")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(total, 40);
+    assert!(roots >= 1, "дерево має мати хоча б один корінь");
+    assert!(children >= 1, "жоден рядок не отримав батька");
+
+    // Батько завжди вставлений раніше за дитину, тож дерево не має циклів
+    let backward: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE parent_id >= id", db.table("categories")))
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(backward, 0);
+
+    // Від кожної категорії можна дійти до кореня
+    let reachable: i64 = sqlx::query_scalar(&format!(
+        "WITH RECURSIVE tree AS (
+            SELECT id FROM {0} WHERE parent_id IS NULL
+            UNION ALL
+            SELECT c.id FROM {0} c JOIN tree t ON c.parent_id = t.id
+        ) SELECT COUNT(*) FROM tree",
+        db.table("categories")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(reachable, 40);
+
+    db.teardown().await;
+}
+
+#[tokio::test]
+async fn rejects_required_self_reference() {
+    let Some(db) = TestDb::setup(
+        "required_self_reference",
+        "CREATE TABLE categories (id serial PRIMARY KEY, parent_id integer NOT NULL REFERENCES categories(id), name text NOT NULL);",
+    )
+    .await
+    else {
+        return;
+    };
+    let config = db.config("[[seeding_plan]]\ntable = \"categories\"\nrows = 5\n");
+
+    let error = db.seed(&config, PLAN).await.expect_err("NOT NULL самопосилання не можна заповнити");
+    assert!(error.to_string().contains("categories.parent_id"), "{}", error);
+
+    db.teardown().await;
+}
@@ -0,0 +1,94 @@
+//! Запуск з пулом з одного з'єднання (`database.max_connections = 1`): поки відкрите з'єднання запуску,
+//! сідер не бере з пулу інших з'єднань
+mod common;
+
+use common::TestDb;
+use db_seeder::{ArchitecturalPlan, DbClient, RunOptions, Seeder};
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+
+const DDL: &str = "CREATE TABLE categories (id serial PRIMARY KEY, name text NOT NULL);
+    INSERT INTO categories (name) VALUES ('books'), ('games'), ('music');
+    CREATE TABLE users (id serial PRIMARY KEY, email text NOT NULL);
+    INSERT INTO users (email) VALUES ('a@example.com'), ('b@example.com');
+    CREATE TABLE products (
+        id serial PRIMARY KEY,
+        category_id integer NOT NULL REFERENCES categories(id),
+        owner_id integer NOT NULL REFERENCES users(id),
+        price integer NOT NULL
+    );";
+
+const PLAN: &str = r#"{"theme":"shop","data_pools":{},"entity_templates":[
+    {"entity_name":"User","target_table":"users","fields":[
+        {"column_name":"email","generator":"template","params":{"format":"user{random_digits:6}@example.com"}}
+    ]},
+    {"entity_name":"Product","target_table":"products","fields":[
+        {"column_name":"category_id","generator":"fk","params":{"references":"categories"}},
+        {"column_name":"owner_id","generator":"fk","params":{"references":"users"}},
+        {"column_name":"price","generator":"number_range","params":{"min":1,"max":100}}
+    ]}
+]}"#;
+
+async fn seed_with_one_connection(name: &str, transaction_mode: &str) {
+    let Some(db) = TestDb::setup(name, DDL).await else {
+        return;
+    };
+    let config = db.config(&format!(
+        r#"[generation]
+language = "en"
+seed = 19
+transaction_mode = "{}"
+analyze_after = true
+
+[[seeding_plan]]
+table = "users"
+rows = 5
+
+[[seeding_plan]]
+table = "products"
+rows = 10
+"#,
+        transaction_mode
+    ));
+
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect(&db.url)
+        .await
+        .expect("пул з одного з'єднання");
+    let seeder = Seeder::new(DbClient::from_pool(pool.clone()).with_schema(&db.schema)).await.unwrap();
+    let options = RunOptions {
+        architectural_plan: Some(serde_json::from_str::<ArchitecturalPlan>(PLAN).unwrap()),
+        seed_only_empty: true,
+        ..RunOptions::default()
+    };
+    let summary = seeder.run(&config, &options).await.expect("заповнення через одне з'єднання");
+    pool.close().await;
+
+    assert_eq!(summary.tables.iter().map(|t| (t.table.as_str(), t.inserted)).collect::<Vec<_>>(), vec![("products", 10)]);
+    assert_eq!(summary.analyzed, vec!["products".to_string()]);
+    let (users, products, orphans): (i64, i64, i64) = sqlx::query_as(&format!(
+        "SELECT (SELECT COUNT(*) FROM {0}), (SELECT COUNT(*) FROM {1}),
+                (SELECT COUNT(*) FROM {1} p WHERE p.owner_id NOT IN (SELECT id FROM {0}) OR p.category_id NOT IN (SELECT id FROM {2}))",
+        db.table("users"),
+        db.table("products"),
+        db.table("categories")
+    ))
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!((users, products, orphans), (2, 10, 0), "users пропущено через seed_only_empty");
+
+    db.teardown().await;
+}
+
+#[tokio::test]
+async fn per_table_mode_needs_one_connection() {
+    seed_with_one_connection("single_connection_per_table", "per_table").await;
+}
+
+#[tokio::test]
+async fn global_mode_needs_one_connection() {
+    seed_with_one_connection("single_connection_global", "global").await;
+}
@@ -0,0 +1,61 @@
+//! `fk` з `unique: true` (зв'язок 1:1): кожен батько використовується рівно один раз, навіть якщо рядок
+//! генерується повторно через інше обмеження
+mod common;
+
+use common::TestDb;
+
+const DDL: &str = "CREATE TABLE users (id serial PRIMARY KEY, email text NOT NULL);
+    CREATE TABLE profiles (
+        id serial PRIMARY KEY,
+        user_id integer NOT NULL UNIQUE REFERENCES users(id),
+        code integer NOT NULL UNIQUE
+    );";
+
+const PLAN: &str = r#"{"theme":"accounts","data_pools":{},"entity_templates":[
+    {"entity_name":"User","target_table":"users","fields":[
+        {"column_name":"email","generator":"template","params":{"format":"user{random_digits:8}@example.com"}}
+    ]},
+    {"entity_name":"Profile","target_table":"profiles","fields":[
+        {"column_name":"user_id","generator":"fk","params":{"references":"users","unique":true}},
+        {"column_name":"code","generator":"number_range","params":{"min":1,"max":7}}
+    ]}
+]}"#;
+
+fn sections(profiles: u32) -> String {
+    format!(
+        "[generation]\nlanguage = \"en\"\nseed = 23\nrow_retries = 50\n\n[[seeding_plan]]\ntable = \"users\"\nrows = 6\n\n[[seeding_plan]]\ntable = \"profiles\"\nrows = {}\n",
+        profiles
+    )
+}
+
+#[tokio::test]
+async fn retried_rows_keep_every_parent() {
+    let Some(db) = TestDb::setup("unique_fk", DDL).await else {
+        return;
+    };
+    // Лише 7 можливих `code` на 6 рядків: частина рядків порушує UNIQUE(code) і генерується повторно
+    let summary = db.seed(&db.config(&sections(6)), PLAN).await.expect("заповнення 1:1 з повторами рядків");
+    assert_eq!(summary.tables.iter().map(|t| t.inserted).collect::<Vec<_>>(), vec![6, 6]);
+
+    let linked: i64 = sqlx::query_scalar(&format!("SELECT COUNT(DISTINCT user_id) FROM {}", db.table("profiles")))
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(linked, 6, "кожен користувач має рівно один профіль");
+
+    db.teardown().await;
+}
+
+#[tokio::test]
+async fn rejects_more_children_than_parents_before_inserting() {
+    let Some(db) = TestDb::setup("unique_fk_shortage", DDL).await else {
+        return;
+    };
+    let error = db.seed(&db.config(&sections(7)), PLAN).await.expect_err("7 профілів на 6 користувачів");
+    assert!(error.to_string().contains("'profiles.user_id': 7 рядків"), "{}", error);
+
+    let users: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", db.table("users"))).fetch_one(&db.pool).await.unwrap();
+    assert_eq!(users, 0, "помилка має виникати до вставки");
+
+    db.teardown().await;
+}